@@ -0,0 +1,38 @@
+//! Walk through the public surface of `BigInt`.
+
+use std::convert::TryFrom;
+
+use karatsuba::{BigInt, ParseBigIntError};
+
+fn main() {
+    let pi: BigInt = "3141592653589793238462643383279502884197169399375105820974944592".parse().unwrap();
+    let e: BigInt = "2718281828459045235360287471352662497757247093699959574966967627".parse().unwrap();
+
+    println!("pi + e  = {}", &pi + &e);
+    println!("pi - e  = {}", &pi - &e);
+    println!("e - pi  = {}", &e - &pi);
+    println!("pi x e  = {}", &pi * &e);
+    println!("-pi     = {}", -&pi);
+    println!("|e - pi| = {}", (&e - &pi).abs());
+    println!("pi > e  = {}", pi > e);
+    println!("digits  = {}", (&pi * &e).digit_count());
+
+    let max = BigInt::from(u64::MAX);
+
+    println!("u64::MAX as i64 = {:?}", i64::try_from(&max));
+    println!("u64::MAX as f64 = {}", max.to_f64());
+    println!("1e30 from f64   = {:?}", BigInt::from_f64(1e30).map(|n| n.to_string()));
+    println!("-129 as bytes   = {:02x?}", BigInt::from(-129).to_signed_bytes_be());
+
+    let zero: BigInt = "-0000".parse().unwrap();
+
+    println!("-0000 parses to {}, is_zero = {}", zero, zero.is_zero());
+
+    match "12a4".parse::<BigInt>() {
+        Ok(n) => println!("unexpectedly parsed {}", n),
+        Err(ParseBigIntError::InvalidDigit { index, found }) => {
+            println!("12a4 has an invalid digit {:?} at {}", found, index)
+        }
+        Err(err) => println!("12a4 failed to parse: {}", err),
+    }
+}
@@ -0,0 +1,29 @@
+//! Multiply two numbers given on the command line.
+//!
+//! cargo run --example multiply -- 3141592653589793 2718281828459045
+
+use std::env;
+use std::process;
+
+use karatsuba::BigInt;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.len() != 2 {
+        eprintln!("usage: multiply <a> <b>");
+        process::exit(2);
+    }
+
+    let parse = |s: &str| -> BigInt {
+        s.parse().unwrap_or_else(|e| {
+            eprintln!("{:?}: {}", s, e);
+            process::exit(1);
+        })
+    };
+
+    let a = parse(&args[0]);
+    let b = parse(&args[1]);
+
+    println!("{}", &a * &b);
+}
@@ -0,0 +1,26 @@
+//! Multiply, divide, evaluate and interpolate polynomials over a prime field.
+
+use karatsuba::modular::ModP;
+use karatsuba::polynomial::Polynomial;
+
+type F = ModP<998244353>;
+
+fn main() {
+    let p = Polynomial::new(vec![F::new(1), F::new(2), F::new(3)]);
+    let q = Polynomial::new(vec![F::from_i64(-1), F::new(1)]);
+
+    let product = &p * &q;
+    let (quotient, remainder) = product.div_rem(&q).unwrap();
+
+    println!("({}) x ({}) = {}", p, q, product);
+    println!("({}) / ({}) = {} remainder {}", product, q, quotient, remainder);
+
+    let xs: Vec<F> = (0..4).map(F::new).collect();
+    let ys = product.evaluate_many(&xs);
+
+    for (x, y) in xs.iter().zip(&ys) {
+        println!("f({}) = {}", x, y);
+    }
+
+    println!("interpolated: {}", Polynomial::interpolate(&xs, &ys).unwrap());
+}
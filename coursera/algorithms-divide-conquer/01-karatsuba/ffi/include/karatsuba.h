@@ -0,0 +1,61 @@
+/*
+ * C interface to the karatsuba arbitrary precision integer library.
+ *
+ * Numbers are opaque handles owned by the caller. A function that fails
+ * returns NULL and leaves a status for bigint_last_error() and a description
+ * for bigint_last_error_message(); one that succeeds sets the status to
+ * BIGINT_OK. The status is kept per thread.
+ *
+ * Link against libkaratsuba_ffi.a (with -lpthread -ldl -lm) or
+ * libkaratsuba_ffi.so.
+ */
+
+#ifndef KARATSUBA_H
+#define KARATSUBA_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct bigint bigint;
+
+/* Status codes returned by bigint_last_error(). */
+#define BIGINT_OK 0
+/* A pointer argument was NULL. */
+#define BIGINT_ERR_NULL 1
+/* A string argument was not valid UTF-8. */
+#define BIGINT_ERR_UTF8 2
+/* A string argument was not a decimal integer. */
+#define BIGINT_ERR_PARSE 3
+/* The library hit an internal error. The message says why. */
+#define BIGINT_ERR_PANIC 4
+
+/* Parse an optionally signed decimal integer, e.g. "-00123". */
+bigint *bigint_from_str(const char *s);
+
+/* a * b, a + b and a - b. Both operands are left as they are. */
+bigint *bigint_mul(const bigint *a, const bigint *b);
+bigint *bigint_add(const bigint *a, const bigint *b);
+bigint *bigint_sub(const bigint *a, const bigint *b);
+
+/* The decimal form of n. Free it with bigint_str_free(), not free(). */
+char *bigint_to_str(const bigint *n);
+
+/* Free a number or a string. NULL is ignored, and the status is kept. */
+void bigint_free(bigint *n);
+void bigint_str_free(char *s);
+
+/* The status of the last call on this thread. */
+int bigint_last_error(void);
+
+/*
+ * A description of the last error on this thread, or NULL after a success.
+ * Owned by the library and valid until the next call that can fail.
+ */
+const char *bigint_last_error_message(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* KARATSUBA_H */
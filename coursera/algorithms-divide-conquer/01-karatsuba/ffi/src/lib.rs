@@ -0,0 +1,345 @@
+//! C bindings for the `karatsuba` crate.
+//!
+//! Built as a static and a shared library, declared for C in
+//! `include/karatsuba.h`. Numbers are opaque `bigint` handles owned by the
+//! caller, created from and turned back into decimal strings:
+//!
+//! ```c
+//! bigint *a = bigint_from_str("-12345678901234567890");
+//! bigint *b = bigint_mul(a, a);
+//! char *text = bigint_to_str(b);
+//!
+//! puts(text);
+//!
+//! bigint_str_free(text);
+//! bigint_free(b);
+//! bigint_free(a);
+//! ```
+//!
+//! A function that fails returns `NULL` and leaves a status code and message
+//! for `bigint_last_error` and `bigint_last_error_message`; one that succeeds
+//! sets the status to `BIGINT_OK`. The status is kept per thread. Panics are
+//! caught at the boundary and reported as `BIGINT_ERR_PANIC`, so they never
+//! unwind into C.
+
+#![warn(missing_docs)]
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use karatsuba::BigInt;
+
+/// The last call succeeded.
+pub const BIGINT_OK: c_int = 0;
+/// A pointer argument was `NULL`.
+pub const BIGINT_ERR_NULL: c_int = 1;
+/// A string argument was not valid UTF-8.
+pub const BIGINT_ERR_UTF8: c_int = 2;
+/// A string argument was not a decimal integer.
+pub const BIGINT_ERR_PARSE: c_int = 3;
+/// The library panicked. The message says why.
+pub const BIGINT_ERR_PANIC: c_int = 4;
+
+/// a failure to report through `bigint_last_error`
+struct Error {
+    code: c_int,
+    message: String,
+}
+
+thread_local! {
+    /// the status of the last call on this thread, and its message
+    static LAST_ERROR: RefCell<(c_int, Option<CString>)> = const { RefCell::new((BIGINT_OK, None)) };
+}
+
+fn set_last_error(error: Option<Error>) {
+    let last = match error {
+        // the message came from Rust strings, which may hold NULs that C cannot
+        Some(Error { code, message }) => (code, Some(CString::new(message.replace('\0', "")).unwrap())),
+        None => (BIGINT_OK, None),
+    };
+
+    LAST_ERROR.with(|cell| *cell.borrow_mut() = last);
+}
+
+/// the message of a caught panic
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        format!("panic: {}", message)
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        format!("panic: {}", message)
+    } else {
+        String::from("panic")
+    }
+}
+
+/// run `f` and record how it went, returning `failed` if it errors or panics
+fn guard<T>(failed: T, f: impl FnOnce() -> Result<T, Error>) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => {
+            set_last_error(None);
+            value
+        }
+        Ok(Err(error)) => {
+            set_last_error(Some(error));
+            failed
+        }
+        Err(payload) => {
+            let message = panic_message(&*payload);
+
+            // dropping the payload runs foreign code that could panic again
+            let _ = panic::catch_unwind(AssertUnwindSafe(move || drop(payload)));
+
+            set_last_error(Some(Error {
+                code: BIGINT_ERR_PANIC,
+                message,
+            }));
+            failed
+        }
+    }
+}
+
+/// borrow the number behind a handle
+unsafe fn borrow<'a>(n: *const BigInt, name: &str) -> Result<&'a BigInt, Error> {
+    n.as_ref().ok_or_else(|| Error {
+        code: BIGINT_ERR_NULL,
+        message: format!("{} is NULL", name),
+    })
+}
+
+/// hand a number to C
+fn into_handle(n: BigInt) -> *mut BigInt {
+    Box::into_raw(Box::new(n))
+}
+
+unsafe fn binary(a: *const BigInt, b: *const BigInt, op: fn(&BigInt, &BigInt) -> BigInt) -> *mut BigInt {
+    guard(ptr::null_mut(), || {
+        let a = borrow(a, "a")?;
+        let b = borrow(b, "b")?;
+
+        Ok(into_handle(op(a, b)))
+    })
+}
+
+/// Parse a NUL-terminated, optionally signed decimal integer.
+///
+/// Returns `NULL` on failure. Free the result with [`bigint_free`].
+///
+/// # Safety
+///
+/// `s` must be `NULL` or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bigint_from_str(s: *const c_char) -> *mut BigInt {
+    guard(ptr::null_mut(), || {
+        if s.is_null() {
+            return Err(Error {
+                code: BIGINT_ERR_NULL,
+                message: String::from("s is NULL"),
+            });
+        }
+
+        let text = CStr::from_ptr(s).to_str().map_err(|e| Error {
+            code: BIGINT_ERR_UTF8,
+            message: e.to_string(),
+        })?;
+
+        let n = text.parse::<BigInt>().map_err(|e| Error {
+            code: BIGINT_ERR_PARSE,
+            message: e.to_string(),
+        })?;
+
+        Ok(into_handle(n))
+    })
+}
+
+/// The product `a * b`, or `NULL` on failure. Free it with [`bigint_free`].
+///
+/// # Safety
+///
+/// `a` and `b` must each be `NULL` or a live handle from this library.
+#[no_mangle]
+pub unsafe extern "C" fn bigint_mul(a: *const BigInt, b: *const BigInt) -> *mut BigInt {
+    binary(a, b, |a, b| a * b)
+}
+
+/// The sum `a + b`, or `NULL` on failure. Free it with [`bigint_free`].
+///
+/// # Safety
+///
+/// `a` and `b` must each be `NULL` or a live handle from this library.
+#[no_mangle]
+pub unsafe extern "C" fn bigint_add(a: *const BigInt, b: *const BigInt) -> *mut BigInt {
+    binary(a, b, |a, b| a + b)
+}
+
+/// The difference `a - b`, or `NULL` on failure. Free it with [`bigint_free`].
+///
+/// # Safety
+///
+/// `a` and `b` must each be `NULL` or a live handle from this library.
+#[no_mangle]
+pub unsafe extern "C" fn bigint_sub(a: *const BigInt, b: *const BigInt) -> *mut BigInt {
+    binary(a, b, |a, b| a - b)
+}
+
+/// The decimal form of `n` as a NUL-terminated string, or `NULL` on failure.
+///
+/// Free it with [`bigint_str_free`], not `free`.
+///
+/// # Safety
+///
+/// `n` must be `NULL` or a live handle from this library.
+#[no_mangle]
+pub unsafe extern "C" fn bigint_to_str(n: *const BigInt) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let n = borrow(n, "n")?;
+
+        // decimal digits and a sign never contain NUL
+        Ok(CString::new(n.to_string()).unwrap().into_raw())
+    })
+}
+
+/// Free a number. Does nothing for `NULL`, and leaves the last error alone.
+///
+/// # Safety
+///
+/// `n` must be `NULL` or a handle from this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bigint_free(n: *mut BigInt) {
+    if !n.is_null() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(n))));
+    }
+}
+
+/// Free a string from [`bigint_to_str`]. Does nothing for `NULL`, and leaves
+/// the last error alone.
+///
+/// # Safety
+///
+/// `s` must be `NULL` or a string from [`bigint_to_str`] that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bigint_str_free(s: *mut c_char) {
+    if !s.is_null() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(CString::from_raw(s))));
+    }
+}
+
+/// The status of the last call on this thread, `BIGINT_OK` if it succeeded.
+#[no_mangle]
+pub extern "C" fn bigint_last_error() -> c_int {
+    LAST_ERROR.with(|cell| cell.borrow().0)
+}
+
+/// A description of the last error on this thread, or `NULL` if the last
+/// call succeeded.
+///
+/// The string belongs to the library and stays valid until the next call on
+/// this thread that can fail.
+#[no_mangle]
+pub extern "C" fn bigint_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|cell| cell.borrow().1.as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> (c_int, Option<String>) {
+        let message = bigint_last_error_message();
+        let message = if message.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_string())
+        };
+
+        (bigint_last_error(), message)
+    }
+
+    /// parse `a` and `b`, apply `op` and return the result as a string
+    fn run_binary_test(
+        a: &str,
+        b: &str,
+        op: unsafe extern "C" fn(*const BigInt, *const BigInt) -> *mut BigInt,
+    ) -> String {
+        unsafe {
+            let a_handle = bigint_from_str(CString::new(a).unwrap().as_ptr());
+            let b_handle = bigint_from_str(CString::new(b).unwrap().as_ptr());
+            let result = op(a_handle, b_handle);
+            let text = bigint_to_str(result);
+
+            assert_eq!(last_error(), (BIGINT_OK, None));
+
+            let owned = CStr::from_ptr(text).to_str().unwrap().to_string();
+
+            println!("[run_binary_test] {} ? {} = {}", a, b, owned);
+
+            bigint_str_free(text);
+            bigint_free(result);
+            bigint_free(b_handle);
+            bigint_free(a_handle);
+
+            owned
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(
+            run_binary_test("-12345678901234567890", "98765432109876543210", bigint_mul),
+            "-1219326311370217952237463801111263526900"
+        );
+        assert_eq!(run_binary_test("999", "1", bigint_add), "1000");
+        assert_eq!(run_binary_test("+0012", "-12", bigint_add), "0");
+        assert_eq!(run_binary_test("5", "8", bigint_sub), "-3");
+    }
+
+    #[test]
+    fn test_errors() {
+        unsafe {
+            assert!(bigint_from_str(ptr::null()).is_null());
+            assert_eq!(last_error(), (BIGINT_ERR_NULL, Some(String::from("s is NULL"))));
+
+            assert!(bigint_from_str(b"12x\0".as_ptr() as *const c_char).is_null());
+            assert_eq!(
+                last_error(),
+                (BIGINT_ERR_PARSE, Some(String::from("invalid digit 'x' at position 2")))
+            );
+
+            assert!(bigint_from_str(b"\xff\0".as_ptr() as *const c_char).is_null());
+            assert_eq!(bigint_last_error(), BIGINT_ERR_UTF8);
+
+            let one = bigint_from_str(b"1\0".as_ptr() as *const c_char);
+
+            assert_eq!(last_error(), (BIGINT_OK, None));
+            assert!(bigint_add(one, ptr::null()).is_null());
+            assert_eq!(last_error(), (BIGINT_ERR_NULL, Some(String::from("b is NULL"))));
+            assert!(bigint_to_str(ptr::null()).is_null());
+            assert_eq!(bigint_last_error(), BIGINT_ERR_NULL);
+
+            // freeing keeps the error for the caller to inspect
+            bigint_free(one);
+            bigint_free(ptr::null_mut());
+            bigint_str_free(ptr::null_mut());
+
+            assert_eq!(bigint_last_error(), BIGINT_ERR_NULL);
+        }
+    }
+
+    #[test]
+    fn test_panics_are_caught() {
+        let result = guard(-1, || -> Result<i32, Error> { panic!("boom {}", 42) });
+
+        assert_eq!(result, -1);
+        assert_eq!(last_error(), (BIGINT_ERR_PANIC, Some(String::from("panic: boom 42"))));
+
+        let result = guard(-1, || -> Result<i32, Error> { std::panic::panic_any(7u8) });
+
+        assert_eq!(result, -1);
+        assert_eq!(last_error(), (BIGINT_ERR_PANIC, Some(String::from("panic"))));
+
+        assert_eq!(guard(-1, || Ok(1)), 1);
+        assert_eq!(last_error(), (BIGINT_OK, None));
+    }
+}
@@ -0,0 +1,60 @@
+//! Compile `tests/c/ffi_test.c` against the static library with the system
+//! C compiler, and run it.
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_c_program() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = manifest_dir.join("..").join("target").join("ffi");
+
+    // a separate target directory avoids waiting on the lock held by this test run
+    let output = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--offline", "--quiet"])
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .output()
+        .expect("failed to run cargo");
+
+    assert!(
+        output.status.success(),
+        "library build failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let program = target_dir.join("ffi_test");
+
+    let output = Command::new("cc")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests").join("c").join("ffi_test.c"))
+        .arg(target_dir.join("debug").join("libkaratsuba_ffi.a"))
+        .args(["-lpthread", "-ldl", "-lm"])
+        .arg("-o")
+        .arg(&program)
+        .output()
+        .expect("failed to run cc, which this test needs on the PATH");
+
+    assert!(
+        output.status.success(),
+        "C compilation failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = Command::new(&program).output().expect("failed to run the C program");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    println!("{}", stdout);
+
+    assert!(
+        output.status.success(),
+        "C program failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.ends_with("ok\n"));
+}
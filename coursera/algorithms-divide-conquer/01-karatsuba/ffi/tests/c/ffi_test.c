@@ -0,0 +1,92 @@
+/* Exercise the C interface the way a C program would. Run by tests/c.rs. */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "karatsuba.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                       \
+    do {                                                                  \
+        if (!(cond)) {                                                    \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,        \
+                    __LINE__, #cond);                                     \
+            failures++;                                                   \
+        }                                                                 \
+    } while (0)
+
+/* apply op to a and b and compare the decimal result with expected */
+static void check_binary(const char *a, const char *b,
+                         bigint *(*op)(const bigint *, const bigint *),
+                         const char *expected) {
+    bigint *x = bigint_from_str(a);
+    bigint *y = bigint_from_str(b);
+    bigint *result = op(x, y);
+    char *text = bigint_to_str(result);
+
+    CHECK(bigint_last_error() == BIGINT_OK);
+    CHECK(bigint_last_error_message() == NULL);
+    CHECK(text != NULL && strcmp(text, expected) == 0);
+
+    printf("[check_binary] %s ? %s = %s\n", a, b, text ? text : "(null)");
+
+    bigint_str_free(text);
+    bigint_free(result);
+    bigint_free(y);
+    bigint_free(x);
+}
+
+int main(void) {
+    check_binary("-12345678901234567890", "98765432109876543210", bigint_mul,
+                 "-1219326311370217952237463801111263526900");
+    check_binary("999", "1", bigint_add, "1000");
+    check_binary("5", "8", bigint_sub, "-3");
+
+    CHECK(bigint_from_str("12x") == NULL);
+    CHECK(bigint_last_error() == BIGINT_ERR_PARSE);
+    CHECK(strcmp(bigint_last_error_message(),
+                 "invalid digit 'x' at position 2") == 0);
+
+    CHECK(bigint_from_str(NULL) == NULL);
+    CHECK(bigint_last_error() == BIGINT_ERR_NULL);
+
+    CHECK(bigint_mul(NULL, NULL) == NULL);
+    CHECK(bigint_last_error() == BIGINT_ERR_NULL);
+    CHECK(bigint_to_str(NULL) == NULL);
+
+    bigint_free(NULL);
+    bigint_str_free(NULL);
+
+    /* a product big enough to take the Karatsuba path */
+    {
+        char digits[2001];
+        bigint *n, *square;
+        char *text;
+
+        memset(digits, '9', 2000);
+        digits[2000] = '\0';
+
+        n = bigint_from_str(digits);
+        square = bigint_mul(n, n);
+        text = bigint_to_str(square);
+
+        /* (10^2000 - 1)^2 = 99...9800...01 */
+        CHECK(text != NULL && strlen(text) == 4000);
+        CHECK(text != NULL && text[1998] == '9' && text[1999] == '8');
+        CHECK(text != NULL && text[3998] == '0' && text[3999] == '1');
+
+        bigint_str_free(text);
+        bigint_free(square);
+        bigint_free(n);
+    }
+
+    if (failures) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+
+    printf("ok\n");
+    return 0;
+}
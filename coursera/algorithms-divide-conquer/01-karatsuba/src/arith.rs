@@ -0,0 +1,1101 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::char;
+use core::cmp::Ordering;
+
+use crate::inplace;
+use crate::kernel::{self, Halves};
+use crate::stats::MulStats;
+use crate::trace::{KaratsubaStep, Method, TraceNode};
+use crate::tuning;
+
+/// products of operands with at most this many digits between them fit in an i128
+const NATIVE_DIGITS: usize = 38;
+
+/// add two arbitrarily large numbers, giving the canonical form of the sum
+pub(crate) fn add(a: &[char], b: &[char]) -> Vec<char> {
+    let a_neg = is_negative(a);
+    let b_neg = is_negative(b);
+
+    let a_abs = abs(a);
+    let b_abs = abs(b);
+
+    let sum = if a_neg {
+        if b_neg {
+            // -a + -b = -(a + b)
+            negate(&add_pos(&a_abs, &b_abs))
+        } else {
+            // -a + b = b - a
+            subtract_pos(&b_abs, &a_abs)
+        }
+    } else {
+        if b_neg {
+            // a + (-b) = a - b
+            subtract_pos(&a_abs, &b_abs)
+        } else {
+            // a + b
+            add_pos(&a_abs, &b_abs)
+        }
+    };
+
+    let sum = canonical(sum);
+
+    debug_assert!(is_canonical(&sum), "non-canonical sum {}", v2s(&sum));
+
+    sum
+}
+
+/// computes a + b, where a, b >= 0
+pub(crate) fn add_pos(a: &[char], b: &[char]) -> Vec<char> {
+    let mut answer = Vec::new();
+
+    let long: &[char];
+    let short: &[char];
+
+    if a.len() >= b.len() {
+        long = a;
+        short = b;
+    } else {
+        long = b;
+        short = a;
+    };
+
+    let mut long_idx = long.len();
+    let mut short_idx = short.len();
+
+    let mut carry: u32 = 0;
+    let mut sum: u32;
+
+    let mut long_val: u32;
+    let mut short_val: u32;
+
+    while long_idx > 0 {
+        long_val = long[long_idx - 1].to_digit(10).unwrap();
+
+        if short_idx > 0 {
+            short_val = short[short_idx - 1].to_digit(10).unwrap();
+        } else {
+            short_val = 0;
+        }
+
+        sum = (long_val + short_val + carry) % 10;
+        carry = (long_val + short_val + carry) / 10;
+
+        answer.push(char::from_digit(sum, 10).unwrap());
+
+        long_idx = if long_idx > 0 { long_idx - 1 } else { 0 };
+        short_idx = if short_idx > 0 { short_idx - 1 } else { 0 };
+    }
+
+    if carry != 0 {
+        answer.push(char::from_digit(carry, 10).unwrap());
+    }
+
+    answer.reverse();
+
+    answer
+}
+
+/// get the magnitude of a number
+pub(crate) fn abs(a: &[char]) -> Vec<char> {
+    if is_negative(a) {
+        a[1..].to_vec()
+    } else {
+        a.to_vec()
+    }
+}
+
+/// negate a number, leaving zero unsigned
+pub(crate) fn negate(a: &[char]) -> Vec<char> {
+    if is_negative(a) {
+        abs(a)
+    } else if a.iter().all(|&d| d == '0') {
+        a.to_vec()
+    } else {
+        let mut v = vec!['-'];
+        v.extend(a);
+        v
+    }
+}
+
+/// check if a number has a unary minus
+pub(crate) fn is_negative(a: &[char]) -> bool {
+    a[0] == '-'
+}
+
+/// left pad with zeros
+pub(crate) fn pad(a: &[char], len: usize) -> Vec<char> {
+    if a.len() > len {
+        panic!("Trying to pad to a shorter length")
+    } else if a.len() == len {
+        a.to_vec()
+    } else {
+        let diff = len - a.len();
+
+        let mut padded = vec!['0'; diff];
+
+        padded.extend(a);
+
+        padded
+    }
+}
+
+/// remove preceding zeros
+pub(crate) fn trim(a: &[char]) ->  Vec<char> {
+    let mut trimmed;
+
+    let is_neg = is_negative(a);
+
+    let a_abs = abs(a);
+
+    if a_abs.len() == 1 {
+        if a_abs[0] == '0' {
+            return vec!['0'];
+        }
+
+        trimmed = a_abs
+    } else {
+        if let Some(first_nonzero) = a_abs.iter().position(|&d| d != '0') {
+            trimmed = a_abs[first_nonzero..].to_vec();
+        } else {
+            return vec!['0'];
+        }
+    }
+
+    if is_neg {
+        trimmed = negate(&trimmed);
+    }
+
+    trimmed
+}
+
+/// whether trimming would change `a`: it has leading zeros or is "-0"
+fn needs_trim(a: &[char]) -> bool {
+    let digits = &a[is_negative(a) as usize..];
+
+    digits.len() > 1 && digits[0] == '0' || is_negative(a) && digits == ['0']
+}
+
+/// trim, without copying numbers that are already in canonical form
+pub(crate) fn canonical(a: Vec<char>) -> Vec<char> {
+    if needs_trim(&a) {
+        trim(&a)
+    } else {
+        a
+    }
+}
+
+/// check the canonical form: an optional '-' and decimal digits, with no
+/// leading zeros, and no sign on zero
+pub(crate) fn is_canonical(a: &[char]) -> bool {
+    let digits = a.strip_prefix(&['-']).unwrap_or(a);
+
+    !digits.is_empty()
+        && digits.iter().all(char::is_ascii_digit)
+        && !needs_trim(a)
+}
+
+/// the sign and the digits of the magnitude without leading zeros, with "-0" unsigned
+fn sign_and_magnitude(a: &[char]) -> (bool, &[char]) {
+    let digits = &a[is_negative(a) as usize..];
+    let first = digits.iter().position(|&d| d != '0').unwrap_or(digits.len() - 1);
+    let digits = &digits[first..];
+
+    (is_negative(a) && digits != ['0'], digits)
+}
+
+/// return true if a >= b
+pub(crate) fn larger(a: &[char], b: &[char]) -> bool {
+    // leading zeros and "-0" would throw off the length and sign checks,
+    // so both are skipped over rather than trimmed off a copy
+    let (a_neg, abs_a) = sign_and_magnitude(a);
+    let (b_neg, abs_b) = sign_and_magnitude(b);
+
+    if a_neg != b_neg {
+        return b_neg;
+    }
+
+    // digits order like their chars, so equal lengths compare lexicographically
+    let magnitude = abs_a.len().cmp(&abs_b.len()).then_with(|| abs_a.cmp(abs_b));
+
+    if a_neg {
+        // equal negatives are still a >= b
+        magnitude != Ordering::Greater
+    } else {
+        magnitude != Ordering::Less
+    }
+}
+
+/// calculate the difference of two arbitrary large numbers, in canonical form
+pub(crate) fn subtract(a: &[char], b: &[char]) -> Vec<char> {
+    let a_neg = is_negative(a);
+    let b_neg = is_negative(b);
+
+    let a_abs = abs(a);
+    let b_abs = abs(b);
+
+    let difference = if a_neg {
+        if b_neg {
+            // -a - -b = -a + b = b - a
+            subtract_pos(&b_abs, &a_abs)
+        } else {
+            // -a - b = -(a + b)
+            negate(&add_pos(&a_abs, &b_abs))
+        }
+    } else {
+        if b_neg {
+            // a - (-b) = a + b
+            add_pos(&a_abs, &b_abs)
+        } else {
+            // a - b
+            subtract_pos(&a_abs, &b_abs)
+        }
+    };
+
+    let difference = canonical(difference);
+
+    debug_assert!(is_canonical(&difference), "non-canonical difference {}", v2s(&difference));
+
+    difference
+}
+
+/// computes a - b, where a >= b and a, b >= 0
+pub(crate) fn subtract_pos(aa: &[char], bb: &[char]) -> Vec<char> {
+    let mut answer = Vec::new();
+
+    let a;
+    let b;
+    let should_negate;
+
+    if larger(aa, bb) {
+        a = aa;
+        b = bb;
+        should_negate = false
+    } else {
+        a = bb;
+        b = aa;
+        should_negate = true;
+    }
+
+    let mut a_idx = a.len();
+    let mut b_idx = b.len();
+
+    let mut difference: u32;
+
+    let mut a_val: u32;
+    let mut b_val: u32;
+
+    let mut a_mut = a.to_vec();
+    while a_idx > 0 {
+        a_val = a_mut[a_idx - 1].to_digit(10).unwrap();
+
+        if b_idx > 0 {
+            b_val = b[b_idx - 1].to_digit(10).unwrap();
+        } else {
+            b_val = 0;
+        }
+
+        difference = if a_val >= b_val {
+            a_val - b_val
+        } else {
+            // perform borrow
+            let mut i = a_idx - 2;
+
+            loop {
+                let lender = a_mut[i].to_digit(10).unwrap();
+
+                if lender > 0 {
+                    a_mut[i] = char::from_digit(lender - 1, 10).unwrap();
+                    break;
+                } else {
+                    a_mut[i] = char::from_digit(9, 10).unwrap();
+                }
+
+                i -= 1;
+            }
+
+            (a_val + 10) - b_val
+        };
+
+        a_idx = if a_idx > 0 { a_idx - 1 } else { 0 };
+        b_idx = if b_idx > 0 { b_idx - 1 } else { 0 };
+
+        if a_idx > 0 || difference != 0 || answer.is_empty() {
+            answer.push(char::from_digit(difference, 10).unwrap());
+        }
+    }
+
+    if should_negate {
+        answer.push('-');
+    }
+
+    answer.reverse();
+
+    trim(&answer)
+}
+
+/// state threaded through the recursion of one multiplication
+#[derive(Default)]
+pub(crate) struct Probe {
+    pub(crate) stats: MulStats,
+    /// the number of enclosing karatsuba calls
+    depth: usize,
+    /// record trace nodes down to this depth
+    trace_depth: Option<usize>,
+    /// the children collected so far by each traced multiply in progress
+    frames: Vec<Vec<TraceNode>>,
+    /// the split made by the karatsuba call that just returned, if traced
+    step: Option<KaratsubaStep>,
+    /// split all the way down to single digits, as done by hand
+    classroom: bool,
+    /// give up on the multiplication once this passes
+    #[cfg(feature = "std")]
+    deadline: Option<std::time::Instant>,
+    /// the deadline passed, so the product is meaningless
+    pub(crate) timed_out: bool,
+}
+
+impl Probe {
+    /// a probe that also records the recursion tree down to `max_depth`
+    pub(crate) fn tracing(max_depth: usize) -> Probe {
+        Probe {
+            trace_depth: Some(max_depth),
+            frames: vec![Vec::new()],
+            ..Probe::default()
+        }
+    }
+
+    /// a probe that records the whole tree of a multiplication split down to single digits
+    pub(crate) fn classroom() -> Probe {
+        Probe {
+            classroom: true,
+            ..Probe::tracing(usize::MAX)
+        }
+    }
+
+    /// a probe that gives up on the multiplication once `deadline` passes
+    #[cfg(feature = "std")]
+    pub(crate) fn until(deadline: Option<std::time::Instant>) -> Probe {
+        Probe {
+            deadline,
+            ..Probe::default()
+        }
+    }
+
+    /// the root of the recorded recursion tree
+    pub(crate) fn into_trace(mut self) -> Option<TraceNode> {
+        self.frames.pop().and_then(|mut roots| roots.pop())
+    }
+
+    /// whether the deadline has passed, remembering once it has
+    pub(crate) fn out_of_time(&mut self) -> bool {
+        #[cfg(feature = "std")]
+        if !self.timed_out && self.deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline) {
+            self.timed_out = true;
+        }
+
+        self.timed_out
+    }
+
+    fn is_tracing(&self) -> bool {
+        self.trace_depth.is_some_and(|max| self.depth <= max)
+    }
+}
+
+/// multiply natively, or with schoolbook or karatsuba in place depending on the operand sizes
+///
+/// The product and all of the recursion's temporaries live in two buffers
+/// allocated up front, so peak memory is linear in the operand lengths.
+pub(crate) fn multiply(a: &[char], b: &[char]) -> Vec<char> {
+    multiply_until(a, b, || false).unwrap()
+}
+
+/// [`multiply`], giving up with `None` if `stop` returns true at a karatsuba step
+pub(crate) fn multiply_until(a: &[char], b: &[char], stop: impl FnMut() -> bool) -> Option<Vec<char>> {
+    let negative = is_negative(a) != is_negative(b);
+
+    let a_abs = &a[is_negative(a) as usize..];
+    let b_abs = &b[is_negative(b) as usize..];
+
+    if a_abs.len() + b_abs.len() <= NATIVE_DIGITS {
+        let a_int = v2s(a).parse::<i128>().unwrap();
+        let b_int = v2s(b).parse::<i128>().unwrap();
+
+        return Some((a_int * b_int).to_string().chars().collect());
+    }
+
+    // one spare digit in front, to hold the sign without shifting the product
+    let mut product = vec!['0'; 1 + a_abs.len() + b_abs.len()];
+    let mut scratch = vec!['0'; inplace::mul_scratch_len(a_abs.len(), b_abs.len())];
+
+    if !inplace::mul_into_until(&mut product[1..], a_abs, b_abs, &mut scratch, stop) {
+        return None;
+    }
+
+    // the digit before the first nonzero one becomes the sign, or is dropped with the leading zeros
+    let first = product[1..].iter().position(|&d| d != '0').unwrap_or(product.len() - 2);
+
+    if negative && product[1 + first..] != ['0'] {
+        product[first] = '-';
+        product.drain(..first);
+    } else {
+        product.drain(..first + 1);
+    }
+
+    debug_assert!(is_canonical(&product), "non-canonical product {}", v2s(&product));
+
+    Some(product)
+}
+
+/// multiply, recording the work done and the recursion tree in `probe`
+pub(crate) fn multiply_probed(a: &[char], b: &[char], probe: &mut Probe) -> Vec<char> {
+    let tracing = probe.is_tracing();
+
+    if tracing {
+        probe.frames.push(Vec::new());
+    }
+
+    let (product, method) = multiply_step(a, b, probe);
+
+    debug_assert!(is_canonical(&product), "non-canonical product {}", v2s(&product));
+
+    if tracing {
+        let children = probe.frames.pop().unwrap();
+        let step = probe.step.take();
+
+        let node = TraceNode {
+            a: v2s(a),
+            b: v2s(b),
+            method,
+            step,
+            result: v2s(&product),
+            pruned: method == Method::Karatsuba && children.is_empty(),
+            children,
+        };
+
+        probe.frames.last_mut().unwrap().push(node);
+    }
+
+    product
+}
+
+/// what a multiplication comes down to once its operands are examined
+enum Step {
+    /// the product, found natively or with schoolbook
+    Done(Vec<char>, Method),
+    /// a karatsuba step on the absolute values padded to the same length,
+    /// with its product negated if the flag is set
+    Split(Vec<char>, Vec<char>, bool),
+}
+
+/// multiply small operands directly, or prepare larger ones for a karatsuba step
+fn prepare(a: &[char], b: &[char], classroom: bool, stats: &mut MulStats) -> Step {
+    let sign_diff = is_negative(a) != is_negative(b);
+
+    let mut a_abs = counted(stats, abs(a));
+    let mut b_abs = counted(stats, abs(b));
+
+    let shortest = core::cmp::min(a_abs.len(), b_abs.len());
+
+    // karatsuba only pays off for longer numbers
+    let base_case = if classroom {
+        shortest == 1
+    } else {
+        shortest < tuning::thresholds().karatsuba
+    };
+
+    if (base_case || !classroom) && a_abs.len() + b_abs.len() <= NATIVE_DIGITS {
+        let a_int = v2s(a).parse::<i128>().unwrap();
+        let b_int = v2s(b).parse::<i128>().unwrap();
+
+        stats.base_products += (a_abs.len() * b_abs.len()) as u64;
+
+        return Step::Done(counted(stats, (a_int * b_int).to_string().chars().collect()), Method::Native);
+    }
+
+    if base_case {
+        stats.base_products += (a_abs.len() * b_abs.len()) as u64;
+
+        return Step::Done(counted(stats, multiply_schoolbook(a, b)), Method::Schoolbook);
+    }
+
+    if a_abs.len() < b_abs.len() {
+        a_abs = counted(stats, pad(&a_abs, b_abs.len()));
+    } else if b_abs.len() < a_abs.len() {
+        b_abs = counted(stats, pad(&b_abs, a_abs.len()));
+    }
+
+    Step::Split(a_abs, b_abs, sign_diff)
+}
+
+/// operands this long still take a karatsuba step `depth` levels down, whatever the tuned threshold
+#[cfg(test)]
+pub(crate) fn karatsuba_digits(depth: u32) -> usize {
+    core::cmp::max(tuning::thresholds().karatsuba, NATIVE_DIGITS / 2 + 1) << depth
+}
+
+fn multiply_step(a: &[char], b: &[char], probe: &mut Probe) -> (Vec<char>, Method) {
+    let (a_abs, b_abs, sign_diff) = match prepare(a, b, probe.classroom, &mut probe.stats) {
+        Step::Done(product, method) => return (product, method),
+        Step::Split(a_abs, b_abs, sign_diff) => (a_abs, b_abs, sign_diff),
+    };
+
+    probe.depth += 1;
+
+    let mut product = karatsuba(&a_abs, &b_abs, probe);
+
+    probe.depth -= 1;
+
+    if sign_diff {
+        product = counted(&mut probe.stats, negate(&product));
+    }
+
+    (product, Method::Karatsuba)
+}
+
+/// tally a freshly allocated number
+fn counted(stats: &mut MulStats, a: Vec<char>) -> Vec<char> {
+    stats.digits_allocated += a.len() as u64;
+    a
+}
+
+/// decimal digits, most significant first, for the shared karatsuba step
+pub(crate) struct Decimal;
+
+impl kernel::Positional for Decimal {
+    type Position = char;
+    type Tally = MulStats;
+
+    fn split(n: &[char], shift: usize, stats: &mut MulStats) -> (Vec<char>, Vec<char>) {
+        let (high, low) = n.split_at(n.len() - shift);
+
+        (counted(stats, trim(high)), counted(stats, trim(low)))
+    }
+
+    fn add(a: &[char], b: &[char], stats: &mut MulStats) -> Vec<char> {
+        counted(stats, add(a, b))
+    }
+
+    fn sub(a: &[char], b: &[char], stats: &mut MulStats) -> Vec<char> {
+        counted(stats, subtract(a, b))
+    }
+
+    fn shift(mut n: Vec<char>, by: usize, stats: &mut MulStats) -> Vec<char> {
+        n.extend(vec!['0'; by]);
+
+        stats.digits_allocated += by as u64;
+
+        n
+    }
+}
+
+/// count a karatsuba step at `depth`, returning the product if it is down to single digits
+fn enter(a: &[char], b: &[char], depth: usize, stats: &mut MulStats) -> Option<Vec<char>> {
+    stats.karatsuba_calls += 1;
+    stats.max_depth = core::cmp::max(stats.max_depth, depth);
+
+    if a.len() == 1 || b.len() == 1 {
+        let a_val: u32 = a.iter().collect::<String>().parse().unwrap();
+        let b_val: u32 = b.iter().collect::<String>().parse().unwrap();
+
+        stats.base_products += 1;
+
+        return Some(counted(stats, (a_val * b_val).to_string().chars().collect::<Vec<char>>()));
+    }
+
+    None
+}
+
+/// z2 * 10^(2 * shift) + z3 * 10^shift + z0, counting the six additions and subtractions of a step
+fn combine(z0: &[char], z2: Vec<char>, z3: Vec<char>, shift: usize, stats: &mut MulStats) -> Vec<char> {
+    stats.add_subs += 6;
+
+    kernel::combine::<Decimal>(z0, z2, z3, shift, stats)
+}
+
+pub(crate) fn karatsuba(a: &[char], b: &[char], probe: &mut Probe) -> Vec<char> {
+    // once out of time, every step unwinds without doing any more work
+    if probe.out_of_time() {
+        return vec!['0'];
+    }
+
+    if let Some(product) = enter(a, b, probe.depth, &mut probe.stats) {
+        return product;
+    }
+
+    let Halves { shift, ah, al, bh, bl, a_sum, b_sum } = kernel::halve::<Decimal>(a, b, &mut probe.stats);
+
+    let z0 = multiply_probed(&al, &bl, probe);
+    let z1 = multiply_probed(&a_sum, &b_sum, probe);
+    let z2 = multiply_probed(&ah, &bh, probe);
+
+    if probe.timed_out {
+        return vec!['0'];
+    }
+
+    let z3 = kernel::cross_terms::<Decimal>(&z0, &z1, &z2, &mut probe.stats);
+
+    if probe.trace_depth.is_some_and(|max| probe.depth - 1 <= max) {
+        probe.step = Some(KaratsubaStep {
+            ah: v2s(&ah),
+            al: v2s(&al),
+            bh: v2s(&bh),
+            bl: v2s(&bl),
+            mid: a.len() - shift,
+            shift,
+            z0: v2s(&z0),
+            z1: v2s(&z1),
+            z2: v2s(&z2),
+            z3: v2s(&z3),
+        });
+    }
+
+    combine(&z0, z2, z3, shift, &mut probe.stats)
+}
+
+/// work left to do by [`multiply_iterative`]
+enum Task {
+    /// multiply two numbers inside this many karatsuba steps, pushing the product
+    Multiply(Vec<char>, Vec<char>, usize),
+    /// pop z2, z1 and z0, and push their combination, negated if the flag is set
+    Combine(usize, bool),
+}
+
+/// multiply like [`multiply`], but from a work stack on the heap instead of by recursion,
+/// so the native stack stays the same size however long the operands are
+pub(crate) fn multiply_iterative(a: &[char], b: &[char], stats: &mut MulStats) -> Vec<char> {
+    let mut tasks = vec![Task::Multiply(a.to_vec(), b.to_vec(), 0)];
+    let mut products: Vec<Vec<char>> = Vec::new();
+
+    while let Some(task) = tasks.pop() {
+        let (product, negative) = match task {
+            Task::Multiply(a, b, depth) => match prepare(&a, &b, false, stats) {
+                Step::Done(product, _) => (product, false),
+                Step::Split(a_abs, b_abs, sign_diff) => match enter(&a_abs, &b_abs, depth + 1, stats) {
+                    Some(product) => (product, sign_diff),
+                    None => {
+                        let halves = kernel::halve::<Decimal>(&a_abs, &b_abs, stats);
+
+                        // z0 is popped, and so computed, first, as in the recursion
+                        tasks.push(Task::Combine(halves.shift, sign_diff));
+                        tasks.push(Task::Multiply(halves.ah, halves.bh, depth + 1));
+                        tasks.push(Task::Multiply(halves.a_sum, halves.b_sum, depth + 1));
+                        tasks.push(Task::Multiply(halves.al, halves.bl, depth + 1));
+
+                        continue;
+                    }
+                },
+            },
+            Task::Combine(shift, sign_diff) => {
+                let z2 = products.pop().unwrap();
+                let z1 = products.pop().unwrap();
+                let z0 = products.pop().unwrap();
+
+                let z3 = kernel::cross_terms::<Decimal>(&z0, &z1, &z2, stats);
+
+                (combine(&z0, z2, z3, shift, stats), sign_diff)
+            }
+        };
+
+        products.push(if negative { counted(stats, negate(&product)) } else { product });
+    }
+
+    let product = products.pop().unwrap();
+
+    debug_assert!(is_canonical(&product), "non-canonical product {}", v2s(&product));
+
+    product
+}
+
+/// multiply two arbitrarily large numbers the grade-school way, in O(n * m) digit products
+pub(crate) fn multiply_schoolbook(a: &[char], b: &[char]) -> Vec<char> {
+    let a_abs = abs(a);
+    let b_abs = abs(b);
+
+    let mut product = vec!['0'; a_abs.len() + b_abs.len()];
+
+    inplace::schoolbook(&mut product, &a_abs, &b_abs);
+
+    let product = trim(&product);
+
+    if is_negative(a) != is_negative(b) && product != ['0'] {
+        negate(&product)
+    } else {
+        product
+    }
+}
+
+/// computes a * m + c, where a >= 0 and m, c are single words
+pub(crate) fn multiply_small(a: &[char], m: u32, c: u32) -> Vec<char> {
+    let mut answer = Vec::new();
+
+    let mut carry = c as u64;
+
+    for digit in a.iter().rev() {
+        let current = digit.to_digit(10).unwrap() as u64 * m as u64 + carry;
+
+        answer.push(char::from_digit((current % 10) as u32, 10).unwrap());
+        carry = current / 10;
+    }
+
+    while carry > 0 {
+        answer.push(char::from_digit((carry % 10) as u32, 10).unwrap());
+        carry /= 10;
+    }
+
+    answer.reverse();
+
+    trim(&answer)
+}
+
+/// computes (a / d, a % d), where a >= 0 and d > 0 is a single word
+pub(crate) fn divide_small(a: &[char], d: u32) -> (Vec<char>, u32) {
+    let mut quotient = Vec::with_capacity(a.len());
+
+    let mut remainder: u64 = 0;
+
+    for digit in a {
+        let current = remainder * 10 + digit.to_digit(10).unwrap() as u64;
+
+        quotient.push(char::from_digit((current / d as u64) as u32, 10).unwrap());
+        remainder = current % d as u64;
+    }
+
+    (trim(&quotient), remainder as u32)
+}
+
+/// long division, truncating toward zero, so the remainder has the sign of a
+pub(crate) fn divide(a: &[char], b: &[char]) -> (Vec<char>, Vec<char>) {
+    let b_abs = abs(b);
+
+    assert!(b_abs != ['0'], "attempt to divide by zero");
+
+    let mut quotient = Vec::with_capacity(a.len());
+    let mut remainder = vec!['0'];
+
+    for &digit in abs(a).iter() {
+        remainder.push(digit);
+        remainder = trim(&remainder);
+
+        let mut q = 0;
+
+        while larger(&remainder, &b_abs) {
+            remainder = subtract_pos(&remainder, &b_abs);
+            q += 1;
+        }
+
+        quotient.push(char::from_digit(q, 10).unwrap());
+    }
+
+    let mut quotient = trim(&quotient);
+
+    if is_negative(a) != is_negative(b) && quotient != ['0'] {
+        quotient = negate(&quotient);
+    }
+
+    if is_negative(a) && remainder != ['0'] {
+        remainder = negate(&remainder);
+    }
+
+    (quotient, remainder)
+}
+
+/// the quotient of a / b, truncated toward zero
+pub(crate) fn quotient(a: &[char], b: &[char]) -> Vec<char> {
+    divide(a, b).0
+}
+
+/// the remainder of a / b, with the sign of a
+pub(crate) fn remainder(a: &[char], b: &[char]) -> Vec<char> {
+    divide(a, b).1
+}
+
+/// converts an array of chars to a string
+pub(crate) fn v2s(a: &[char]) -> String {
+    a.iter().collect::<String>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_add_test(a: i128, b: i128) {
+        let sum = a + b;
+
+        println!("[run_add_test] {:?} + {:?} = {}", a, b, sum);
+
+        assert_eq!(add(&a.to_string().chars().collect::<Vec<char>>(), &b.to_string().chars().collect::<Vec<char>>()),
+                   sum.to_string().chars().collect::<Vec<char>>());
+    }
+
+    #[test]
+    #[allow(clippy::zero_prefixed_literal)]
+    fn test_add() {
+        run_add_test(-1, 2);
+        run_add_test(0, 0);
+        run_add_test(123, 345);
+        run_add_test(999, 2);
+        run_add_test(0, -99);
+        run_add_test(123123123123, 5345345345);
+        run_add_test(314159265358979323840974944592, 5345345345);
+        run_add_test(31415926535897944592, -065518096806127861769640336);
+    }
+
+    #[test]
+    fn test_add_negatives() {
+        // both negative adds the magnitudes, where it used to subtract them
+        run_add_test(-123, -877);
+        run_add_test(-877, -123);
+        run_add_test(-5, -5);
+        run_add_test(-1, -999999999999999999999999);
+        run_add_test(-31415926535897944592, -65518096806127861769640336);
+    }
+
+    fn run_larger_test(a: i128, b: i128) {
+        println!("[run_larger_test] {:?} >= {:?} = {}", a, b, a >= b);
+
+        assert_eq!(larger(&a.to_string().chars().collect::<Vec<char>>(), &b.to_string().chars().collect::<Vec<char>>()),
+                   a >= b);
+    }
+
+    #[test]
+    fn test_larger() {
+        run_larger_test(10, 1);
+        run_larger_test(0, 1);
+        run_larger_test(0, 0);
+        run_larger_test(-99, 0);
+        run_larger_test(-1, 0);
+        run_larger_test(1, -2);
+        run_larger_test(123123123, 123123122);
+        run_larger_test(-123123123, -123123122);
+        run_larger_test(22, 22);
+        run_larger_test(43426, 85992);
+        run_larger_test(33, 33);
+    }
+
+    #[test]
+    fn test_trim() {
+        assert_eq!(trim(&"01".chars().collect::<Vec<char>>()), "1".chars().collect::<Vec<char>>());
+        assert_eq!(trim(&"-01".chars().collect::<Vec<char>>()), "-1".chars().collect::<Vec<char>>());
+        assert_eq!(trim(&"999".chars().collect::<Vec<char>>()), "999".chars().collect::<Vec<char>>());
+        assert_eq!(trim(&"0".chars().collect::<Vec<char>>()), "0".chars().collect::<Vec<char>>());
+        assert_eq!(trim(&"-0100".chars().collect::<Vec<char>>()), "-100".chars().collect::<Vec<char>>());
+        assert_eq!(trim(&"00000100".chars().collect::<Vec<char>>()), "100".chars().collect::<Vec<char>>());
+        assert_eq!(trim(&"-0".chars().collect::<Vec<char>>()), "0".chars().collect::<Vec<char>>());
+    }
+
+    #[test]
+    fn test_canonical() {
+        let chars = |s: &str| s.chars().collect::<Vec<char>>();
+
+        for s in ["0", "7", "-7", "10", "-1000"] {
+            assert!(is_canonical(&chars(s)), "{}", s);
+        }
+
+        for s in ["", "-", "-0", "00", "-012", "0012", "1-2", "+1"] {
+            assert!(!is_canonical(&chars(s)), "{}", s);
+        }
+
+        assert_eq!(canonical(chars("-000")), chars("0"));
+        assert_eq!(canonical(chars("-0120")), chars("-120"));
+        assert_eq!(negate(&chars("0")), chars("0"));
+        assert_eq!(add(&chars("000"), &chars("-0")), chars("0"));
+        assert_eq!(add(&chars("0012"), &chars("0")), chars("12"));
+        assert_eq!(subtract(&chars("-0"), &chars("00")), chars("0"));
+
+        // a zero product of a karatsuba step, with the operand signs differing
+        let long = chars(&format!("-{}", "0".repeat(200)));
+        let product = multiply(&long, &vec!['7'; 200]);
+
+        println!("[test_canonical] -0 * 77...7 = {}", v2s(&product));
+
+        assert_eq!(product, chars("0"));
+        assert_eq!(multiply_iterative(&long, &vec!['7'; 200], &mut MulStats::default()), chars("0"));
+    }
+
+    fn run_subtract_test(a: i128, b: i128) {
+        let difference = a - b;
+
+        println!("[run_subtract_test] {:?} - {:?} = {}", a, b, difference);
+
+        assert_eq!(subtract(&a.to_string().chars().collect::<Vec<char>>(), &b.to_string().chars().collect::<Vec<char>>()),
+                   difference.to_string().chars().collect::<Vec<char>>());
+    }
+
+    #[test]
+    fn test_subtract() {
+        run_subtract_test(1, 1);
+        run_subtract_test(1152, 1092);
+        run_subtract_test(456, 123);
+        run_subtract_test(88, -88912312);
+        run_subtract_test(0, 2312);
+        run_subtract_test(44, 1);
+        run_subtract_test(-44, 8123123);
+        run_subtract_test(-10001, 99);
+        run_subtract_test(10000, 999);
+        run_subtract_test(99, 99);
+        run_subtract_test(81345400, 487254);
+    }
+
+    fn run_multiply_test(a: i128, b: i128) {
+        let product = a * b;
+
+        println!("[run_multiply_test] {:?} x {:?} = {}", a, b, product);
+
+        assert_eq!(multiply(&a.to_string().chars().collect::<Vec<char>>(), &b.to_string().chars().collect::<Vec<char>>()),
+                   product.to_string().chars().collect::<Vec<char>>());
+    }
+
+    #[test]
+    fn test_multiply() {
+        run_multiply_test(123, 456);
+        run_multiply_test(1234, 4321);
+        run_multiply_test(-1234, 4321);
+        run_multiply_test(114, 48);
+        run_multiply_test(9123, 1236);
+        run_multiply_test(1000, 1001);
+        run_multiply_test(1234, -11114321);
+        run_multiply_test(1234, 12345);
+        run_multiply_test(10000, 1000);
+        run_multiply_test(123123123188888231, 101239999777700);
+        run_multiply_test(123, 12345678901);
+        run_multiply_test(-5, 0);
+        run_multiply_test(99999999999999999999, 999999999999999999);
+    }
+
+    fn run_schoolbook_test(a: &str, b: &str) {
+        let a = a.chars().collect::<Vec<char>>();
+        let b = b.chars().collect::<Vec<char>>();
+
+        let product = multiply_schoolbook(&a, &b);
+
+        println!("[run_schoolbook_test] {} x {} = {}", v2s(&a), v2s(&b), v2s(&product));
+
+        assert_eq!(product, multiply(&a, &b));
+    }
+
+    #[test]
+    fn test_multiply_schoolbook() {
+        run_schoolbook_test("0", "-123");
+        run_schoolbook_test("-7", "-8");
+        run_schoolbook_test("00123", "-456");
+        run_schoolbook_test("123", "98765432109876543210987654321098765432109876543210");
+        run_schoolbook_test("3141592653589793238462643383279502884197169399375105820974944592",
+                            "-2718281828459045235360287471352662497757247093699959574966967627");
+        run_schoolbook_test(&"9".repeat(300), &"9".repeat(150));
+    }
+
+    fn run_divide_test(a: i128, b: i128) {
+        let (q, r) = divide(&a.to_string().chars().collect::<Vec<char>>(), &b.to_string().chars().collect::<Vec<char>>());
+
+        println!("[run_divide_test] {:?} / {:?} = {}, {}", a, b, v2s(&q), v2s(&r));
+
+        assert_eq!(v2s(&q), (a / b).to_string());
+        assert_eq!(v2s(&r), (a % b).to_string());
+    }
+
+    #[test]
+    fn test_divide() {
+        run_divide_test(0, 7);
+        run_divide_test(7, 7);
+        run_divide_test(6, 7);
+        run_divide_test(-7, 2);
+        run_divide_test(7, -2);
+        run_divide_test(-7, -2);
+        run_divide_test(1000000, 1000);
+        run_divide_test(31415926535897932384626433832795028841, 27182818284590452353);
+        run_divide_test(-99999999999999999999999999999999, 99999999999);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to divide by zero")]
+    fn test_divide_by_zero() {
+        divide(&['1'], &['-', '0']);
+    }
+
+    #[test]
+    fn test_multiply_random() {
+        use crate::random::{random_bigint, Rng};
+
+        let mut rng = Rng::new(2024);
+
+        for _ in 0..40 {
+            let a_len = rng.below_u64(120) as usize + 1;
+            let b_len = rng.below_u64(120) as usize + 1;
+
+            let a = random_bigint(a_len, &mut rng).to_string();
+            let b = random_bigint(b_len, &mut rng).to_string();
+
+            let a: Vec<char> = a.chars().collect();
+            let b = negate(&b.chars().collect::<Vec<char>>());
+
+            println!("[test_multiply_random] {} digits x {} digits", a_len, b_len);
+
+            assert_eq!(multiply(&a, &b), multiply_schoolbook(&a, &b));
+            assert_eq!(multiply(&a, &b), multiply_probed(&a, &b, &mut Probe::default()));
+        }
+    }
+
+    #[test]
+    fn test_multiply_iterative() {
+        use crate::random::{random_bigint, Rng};
+
+        let mut rng = Rng::new(42);
+
+        for _ in 0..30 {
+            let a_len = rng.below_u64(300) as usize + 1;
+            let b_len = rng.below_u64(300) as usize + 1;
+
+            let a: Vec<char> = random_bigint(a_len, &mut rng).to_string().chars().collect();
+            let b: Vec<char> = random_bigint(b_len, &mut rng).to_string().chars().collect();
+            let b = if rng.below_u64(2) == 0 { negate(&b) } else { b };
+
+            let mut probe = Probe::default();
+            let mut stats = MulStats::default();
+
+            let recursive = multiply_probed(&a, &b, &mut probe);
+            let iterative = multiply_iterative(&a, &b, &mut stats);
+
+            println!("[test_multiply_iterative] {} digits x {} digits, {:?}", a_len, b_len, stats);
+
+            // the same steps, so the same work as well as the same product
+            assert_eq!(iterative, recursive);
+            assert_eq!(stats, probe.stats);
+        }
+
+        assert_eq!(multiply_iterative(&['0'], &['-', '7'], &mut MulStats::default()), vec!['0']);
+    }
+
+    #[test]
+    fn test_multiply_iterative_tiny_stack() {
+        let a = vec!['7'; 4000];
+        let b = negate(&vec!['3'; 3000]);
+
+        let expected = multiply(&a, &b);
+
+        // far too little stack for one frame per karatsuba step and its temporaries
+        let product = std::thread::Builder::new()
+            .stack_size(32 * 1024)
+            .spawn(move || multiply_iterative(&a, &b, &mut MulStats::default()))
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(product, expected);
+    }
+
+    #[test]
+    fn test_small() {
+        let a = "98765432109876543210".chars().collect::<Vec<char>>();
+
+        assert_eq!(v2s(&multiply_small(&a, 256, 7)), "25283950620128395061767");
+        assert_eq!(v2s(&multiply_small(&['0'], 4294967295, 0)), "0");
+        assert_eq!(v2s(&multiply_small(&['9'], 4294967295, 4294967295)), "42949672950");
+
+        let (q, r) = divide_small(&a, 256);
+
+        assert_eq!((v2s(&q), r), ("385802469179205246".to_string(), 234));
+        assert_eq!(divide_small(&['7'], 4294967295), (vec!['0'], 7));
+    }
+}
\ No newline at end of file
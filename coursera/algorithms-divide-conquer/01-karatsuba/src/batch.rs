@@ -0,0 +1,402 @@
+//! the `batch` command: one job per line on stdin, one result per line on stdout
+//!
+//! A job is either text, `mul 12 34`, or a flat JSON object,
+//! `{"id": "j1", "op": "mul", "a": "12", "b": "34"}`. The operations are
+//! `add`, `sub`, `mul`, `div` and `rem`. Results are written in the form of
+//! their job:
+//!
+//! ```text
+//! 1 ok 408
+//! 2 error unknown operation pow
+//! {"id":"j1","result":"408"}
+//! {"id":"j2","error":"division by zero"}
+//! ```
+//!
+//! Text jobs are identified by their line number, and so are JSON jobs
+//! without an `id`. Blank lines and lines starting with `#` are skipped, but
+//! still counted.
+
+use std::io::{self, BufRead, Write};
+
+use karatsuba::BigInt;
+
+/// a JSON value, as far as jobs need them
+#[derive(Debug, PartialEq)]
+enum Json {
+    String(String),
+    /// the number exactly as written
+    Number(String),
+}
+
+impl Json {
+    /// the value written back out as JSON
+    fn to_json(&self) -> String {
+        match self {
+            Json::String(s) => quote(s),
+            Json::Number(n) => n.clone(),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Json::String(s) | Json::Number(s) => s,
+        }
+    }
+}
+
+/// a JSON string literal
+fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// returns true if `s` is a JSON number, `-?(0|[1-9]\d*)(\.\d+)?([eE][+-]?\d+)?`
+fn is_number(s: &str) -> bool {
+    let mut rest = s.strip_prefix('-').unwrap_or(s).as_bytes();
+
+    // the length of the run of digits at the start of `rest`
+    let digits = |rest: &[u8]| rest.iter().take_while(|b| b.is_ascii_digit()).count();
+
+    let int = digits(rest);
+
+    if int == 0 || (int > 1 && rest[0] == b'0') {
+        return false;
+    }
+
+    rest = &rest[int..];
+
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let n = digits(fraction);
+
+        if n == 0 {
+            return false;
+        }
+
+        rest = &fraction[n..];
+    }
+
+    if let Some(exponent) = rest.strip_prefix(b"e").or_else(|| rest.strip_prefix(b"E")) {
+        let exponent = exponent.strip_prefix(b"+").or_else(|| exponent.strip_prefix(b"-")).unwrap_or(exponent);
+        let n = digits(exponent);
+
+        if n == 0 {
+            return false;
+        }
+
+        rest = &exponent[n..];
+    }
+
+    rest.is_empty()
+}
+
+/// reads one flat JSON object of strings and numbers
+struct JsonReader<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> JsonReader<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("expected {:?} but found {:?}", expected, c)),
+            None => Err(format!("expected {:?} but the line ended", expected)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut s = String::new();
+
+        loop {
+            match self.chars.next().ok_or("unterminated string")? {
+                '"' => return Ok(s),
+                '\\' => {
+                    let c = match self.chars.next().ok_or("unterminated string")? {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let hex: String = self.chars.by_ref().take(4).collect();
+
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| format!("invalid escape \\u{}", hex))?
+                        }
+                        c => return Err(format!("invalid escape \\{}", c)),
+                    };
+
+                    s.push(c);
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some(c) if *c == '-' || c.is_ascii_digit() => {
+                let mut n = String::new();
+
+                while let Some(&c) = self.chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || "+-.".contains(c)) {
+                        break;
+                    }
+
+                    n.push(c);
+                    self.chars.next();
+                }
+
+                if is_number(&n) {
+                    Ok(Json::Number(n))
+                } else {
+                    Err(format!("invalid number {:?}", n))
+                }
+            }
+            Some(c) => Err(format!("unsupported value starting with {:?}", c)),
+            None => Err(String::from("expected a value but the line ended")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Vec<(String, Json)>, String> {
+        self.expect('{')?;
+        self.skip_whitespace();
+
+        let mut fields = Vec::new();
+
+        if self.chars.peek() == Some(&'}') {
+            self.chars.next();
+        } else {
+            loop {
+                let key = self.string()?;
+
+                self.expect(':')?;
+
+                fields.push((key, self.value()?));
+
+                self.skip_whitespace();
+
+                match self.chars.next() {
+                    Some(',') => continue,
+                    Some('}') => break,
+                    Some(c) => return Err(format!("expected ',' or '}}' but found {:?}", c)),
+                    None => return Err(String::from("expected ',' or '}' but the line ended")),
+                }
+            }
+        }
+
+        self.skip_whitespace();
+
+        match self.chars.next() {
+            None => Ok(fields),
+            Some(c) => Err(format!("unexpected {:?} after the object", c)),
+        }
+    }
+}
+
+/// run one job
+fn evaluate(op: &str, a: &str, b: &str) -> Result<BigInt, String> {
+    if !["add", "sub", "mul", "div", "rem"].contains(&op) {
+        return Err(format!("unknown operation {}", op));
+    }
+
+    let parse = |s: &str| s.parse::<BigInt>().map_err(|e| format!("invalid number {:?}: {}", s, e));
+
+    let a = parse(a)?;
+    let b = parse(b)?;
+
+    if (op == "div" || op == "rem") && b.is_zero() {
+        return Err(String::from("division by zero"));
+    }
+
+    match op {
+        "add" => Ok(a + b),
+        "sub" => Ok(a - b),
+        "mul" => Ok(a * b),
+        "div" => Ok(a / b),
+        _ => Ok(a % b),
+    }
+}
+
+fn text_job(line: &str, id: usize) -> String {
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    let result = match words.as_slice() {
+        [op, a, b] => evaluate(op, a, b),
+        [op, ..] => Err(format!("{} takes two operands, got {}", op, words.len() - 1)),
+        [] => unreachable!(),
+    };
+
+    match result {
+        Ok(value) => format!("{} ok {}", id, value),
+        Err(message) => format!("{} error {}", id, message),
+    }
+}
+
+fn json_job(line: &str, id: usize) -> String {
+    let fields = JsonReader { chars: line.chars().peekable() }.object();
+
+    let field = |name: &str| {
+        fields
+            .as_ref()
+            .ok()
+            .and_then(|fields| fields.iter().find(|(key, _)| key == name))
+            .map(|(_, value)| value)
+    };
+
+    let id = field("id").map_or_else(|| id.to_string(), Json::to_json);
+
+    let result = fields.as_ref().map_err(|e| format!("invalid JSON: {}", e)).and_then(|_| {
+        match (field("op"), field("a"), field("b")) {
+            (Some(op), Some(a), Some(b)) => evaluate(op.as_str(), a.as_str(), b.as_str()),
+            _ => Err(String::from("a job needs \"op\", \"a\" and \"b\"")),
+        }
+    });
+
+    match result {
+        Ok(value) => format!("{{\"id\":{},\"result\":\"{}\"}}", id, value),
+        Err(message) => format!("{{\"id\":{},\"error\":{}}}", id, quote(&message)),
+    }
+}
+
+/// the result record for one line, or `None` if the line holds no job
+fn job(line: &str, id: usize) -> Option<String> {
+    let line = line.trim();
+
+    if line.is_empty() || line.starts_with('#') {
+        None
+    } else if line.starts_with('{') {
+        Some(json_job(line, id))
+    } else {
+        Some(text_job(line, id))
+    }
+}
+
+/// the result record for one raw line, which may not be valid UTF-8
+fn raw_job(line: &[u8], id: usize) -> Option<String> {
+    match std::str::from_utf8(line) {
+        Ok(line) => job(line, id),
+        Err(_) => Some(format!("{} error invalid UTF-8", id)),
+    }
+}
+
+/// answer every job on stdin until it ends
+pub fn run() -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut input = stdin.lock();
+    let mut out = stdout.lock();
+
+    let mut line = Vec::new();
+    let mut id = 0;
+
+    // raw lines, so one that is not UTF-8 gets an error record instead of ending the batch
+    while input.read_until(b'\n', &mut line)? > 0 {
+        id += 1;
+
+        if let Some(record) = raw_job(&line, id) {
+            // stdout is line buffered, so each record goes out as soon as it is ready
+            writeln!(out, "{}", record)?;
+        }
+
+        line.clear();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_job_test(line: &str, expected: &str) {
+        let record = job(line, 7).unwrap();
+
+        println!("[run_job_test] {} -> {}", line, record);
+
+        assert_eq!(record, expected);
+    }
+
+    #[test]
+    fn test_text_jobs() {
+        run_job_test("mul 1234 5678", "7 ok 7006652");
+        run_job_test("  add -5   3 ", "7 ok -2");
+        run_job_test("div 100 -7", "7 ok -14");
+        run_job_test("rem 1 0", "7 error division by zero");
+        run_job_test("pow 2 x", "7 error unknown operation pow");
+        run_job_test("mul 1", "7 error mul takes two operands, got 1");
+        run_job_test("mul 1 x", "7 error invalid number \"x\": invalid digit 'x' at position 0");
+
+        assert_eq!(job("", 1), None);
+        assert_eq!(job("# comment", 1), None);
+    }
+
+    #[test]
+    fn test_json_jobs() {
+        run_job_test(r#"{"op":"mul","a":"12","b":"34"}"#, r#"{"id":7,"result":"408"}"#);
+        run_job_test(r#"{ "id" : "j\"1", "op": "sub", "a": -5, "b": "10" }"#, r#"{"id":"j\"1","result":"-15"}"#);
+        run_job_test(r#"{"id":42,"op":"add","a":"1","b":1.5}"#, r#"{"id":42,"error":"invalid number \"1.5\": invalid digit '.' at position 1"}"#);
+        run_job_test(r#"{"id":1,"op":"mul","a":"2"}"#, r#"{"id":1,"error":"a job needs \"op\", \"a\" and \"b\""}"#);
+        run_job_test(r#"{"op":"mul","a":"2",}"#, r#"{"id":7,"error":"invalid JSON: expected '\"' but found '}'"}"#);
+        run_job_test(r#"{"op":"mul","a":[1]}"#, r#"{"id":7,"error":"invalid JSON: unsupported value starting with '['"}"#);
+        run_job_test(r#"{"op":"mul"} x"#, r#"{"id":7,"error":"invalid JSON: unexpected 'x' after the object"}"#);
+        run_job_test(r#"{"op":"mul","a":"3","b":"4"}"#, r#"{"id":7,"result":"12"}"#);
+        run_job_test(r#"{"id":12abc,"op":"mul","a":"1","b":"2"}"#, r#"{"id":7,"error":"invalid JSON: invalid number \"12abc\""}"#);
+        run_job_test(r#"{"id":-1.5e3,"op":"mul","a":"1","b":"2"}"#, r#"{"id":-1.5e3,"result":"2"}"#);
+    }
+
+    #[test]
+    fn test_raw_jobs() {
+        assert_eq!(raw_job(b"mul 2 3\r\n", 1), Some(String::from("1 ok 6")));
+        assert_eq!(raw_job(b"mul \xff 2\n", 2), Some(String::from("2 error invalid UTF-8")));
+        assert_eq!(raw_job(b"{\"a\":\"\xc3\"}\n", 3), Some(String::from("3 error invalid UTF-8")));
+        assert_eq!(raw_job(b"\n", 4), None);
+    }
+
+    #[test]
+    fn test_is_number() {
+        for n in ["0", "-0", "12", "-3.25", "1e9", "1E+2", "0.5e-07"] {
+            assert!(is_number(n), "{}", n);
+        }
+
+        for n in ["", "-", "12abc", "012", "1.", ".5", "1e", "1e+", "--1", "1.2.3", "0x10"] {
+            assert!(!is_number(n), "{}", n);
+        }
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+}
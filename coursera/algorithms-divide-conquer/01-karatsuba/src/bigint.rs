@@ -0,0 +1,452 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use core::str::FromStr;
+
+use crate::arith;
+use crate::polynomial::Ring;
+use crate::stats::MulStats;
+
+/// An arbitrarily large signed integer.
+///
+/// Values are always kept in canonical form: there are no leading zeros, and
+/// zero has no sign. Every operation returns canonical values, so equal
+/// numbers have equal digits, and compare and hash equal.
+///
+/// ```
+/// use karatsuba::BigInt;
+///
+/// let a: BigInt = "123456789123456789".parse().unwrap();
+/// let b: BigInt = "-987654321".parse().unwrap();
+///
+/// assert_eq!((&a * &b).to_string(), "-121932631234567900112635269");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    digits: Vec<char>,
+}
+
+/// The error returned when parsing a [`BigInt`] fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseBigIntError {
+    /// The input had no digits.
+    Empty,
+    /// The character at `index` is not a decimal digit.
+    InvalidDigit {
+        /// Byte offset of the offending character.
+        index: usize,
+        /// The offending character.
+        found: char,
+    },
+}
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseBigIntError::Empty => write!(f, "cannot parse integer from empty string"),
+            ParseBigIntError::InvalidDigit { index, found } => {
+                write!(f, "invalid digit {:?} at position {}", found, index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseBigIntError {}
+
+impl BigInt {
+    /// The value `0`.
+    pub fn zero() -> BigInt {
+        BigInt::canonical(vec!['0'])
+    }
+
+    /// The value `1`.
+    pub fn one() -> BigInt {
+        BigInt::canonical(vec!['1'])
+    }
+
+    /// wrap digits that are in canonical form already
+    fn canonical(digits: Vec<char>) -> BigInt {
+        debug_assert!(arith::is_canonical(&digits), "non-canonical digits {}", arith::v2s(&digits));
+
+        BigInt { digits }
+    }
+
+    /// Build from already-validated digits, restoring the canonical form.
+    pub(crate) fn from_digits(digits: Vec<char>) -> BigInt {
+        BigInt::canonical(arith::canonical(digits))
+    }
+
+    /// The decimal digits, with a leading `'-'` if negative.
+    pub(crate) fn digits(&self) -> &[char] {
+        &self.digits
+    }
+
+    /// Returns `true` if the value is less than zero.
+    pub fn is_negative(&self) -> bool {
+        arith::is_negative(&self.digits)
+    }
+
+    /// Returns `true` if the value is zero.
+    pub fn is_zero(&self) -> bool {
+        self.digits == ['0']
+    }
+
+    /// The absolute value.
+    pub fn abs(&self) -> BigInt {
+        BigInt::canonical(arith::abs(&self.digits))
+    }
+
+    /// Returns `true` if the value is in canonical form: no leading zeros,
+    /// and no sign on zero.
+    ///
+    /// This always holds, since every way of making a `BigInt` restores the
+    /// canonical form. It is here to check that promise, e.g. in tests of
+    /// code built on this crate.
+    ///
+    /// ```
+    /// use karatsuba::BigInt;
+    ///
+    /// let n: BigInt = "-000".parse().unwrap();
+    ///
+    /// assert!(n.is_canonical());
+    /// assert_eq!(n, BigInt::zero());
+    /// assert!((-&n).is_canonical());
+    /// ```
+    pub fn is_canonical(&self) -> bool {
+        arith::is_canonical(&self.digits)
+    }
+
+    /// Multiply with the O(n²) schoolbook method.
+    ///
+    /// Always gives the same result as `*`, which switches to Karatsuba for
+    /// large operands, so this serves as a reference implementation.
+    pub fn mul_schoolbook(&self, other: &BigInt) -> BigInt {
+        BigInt::from_digits(arith::multiply_schoolbook(&self.digits, &other.digits))
+    }
+
+    /// Multiply with Karatsuba driven from a work stack on the heap.
+    ///
+    /// Gives the same result as `*`, doing the same steps in the same order,
+    /// but without recursing, so very long operands are safe to multiply on
+    /// threads with small stacks.
+    ///
+    /// ```
+    /// use karatsuba::BigInt;
+    ///
+    /// let a: BigInt = "9".repeat(300).parse().unwrap();
+    ///
+    /// assert_eq!(a.mul_iterative(&-&a), &a * &-&a);
+    /// ```
+    pub fn mul_iterative(&self, other: &BigInt) -> BigInt {
+        BigInt::from_digits(arith::multiply_iterative(&self.digits, &other.digits, &mut MulStats::default()))
+    }
+
+    /// The quotient and remainder of division, as `(self / other, self % other)`.
+    ///
+    /// Division truncates toward zero like the primitive integers, so the
+    /// remainder has the sign of `self`.
+    ///
+    /// # Panics
+    ///
+    /// If `other` is zero.
+    pub fn div_rem(&self, other: &BigInt) -> (BigInt, BigInt) {
+        let (quotient, remainder) = arith::divide(&self.digits, &other.digits);
+
+        (BigInt::from_digits(quotient), BigInt::from_digits(remainder))
+    }
+
+    /// Raise to the power `exp` by repeated squaring, with `0^0 = 1`.
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut result = BigInt::one();
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+
+            exp >>= 1;
+
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+
+        result
+    }
+
+    /// The number of decimal digits in the absolute value.
+    pub fn digit_count(&self) -> usize {
+        self.digits.len() - self.is_negative() as usize
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    /// Parse an optionally signed decimal integer, e.g. `"-00123"`.
+    fn from_str(s: &str) -> Result<BigInt, ParseBigIntError> {
+        let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+        let offset = s.len() - unsigned.len();
+
+        if unsigned.is_empty() {
+            return Err(ParseBigIntError::Empty);
+        }
+
+        if let Some((index, found)) = unsigned.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+            return Err(ParseBigIntError::InvalidDigit {
+                index: index + offset,
+                found,
+            });
+        }
+
+        let mut digits: Vec<char> = unsigned.chars().collect();
+
+        if s.starts_with('-') {
+            digits.insert(0, '-');
+        }
+
+        Ok(BigInt::from_digits(digits))
+    }
+}
+
+impl fmt::Display for BigInt {
+    /// Honours the width, fill, alignment, `+` and `0` flags like the
+    /// primitive integers do, e.g. `format!("{:+08}", n)`.
+    ///
+    /// See [`BigInt::format_with`] for digit grouping, scientific notation
+    /// and shortening huge numbers.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = arith::v2s(&self.digits[self.is_negative() as usize..]);
+
+        f.pad_integral(!self.is_negative(), "", &digits)
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        if self == other {
+            Ordering::Equal
+        } else if arith::larger(&self.digits, &other.digits) {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_digits(arith::negate(&self.digits))
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    /// Flips the sign of the digits it owns, without copying them.
+    fn neg(mut self) -> BigInt {
+        if self.is_negative() {
+            self.digits.remove(0);
+        } else if !self.is_zero() {
+            self.digits.insert(0, '-');
+        }
+
+        self
+    }
+}
+
+/// implement a binary operator for every combination of owned and borrowed operands
+macro_rules! impl_binop {
+    ($trait:ident, $method:ident, $f:path) => {
+        impl $trait<&BigInt> for &BigInt {
+            type Output = BigInt;
+
+            fn $method(self, other: &BigInt) -> BigInt {
+                BigInt::from_digits($f(&self.digits, &other.digits))
+            }
+        }
+
+        impl $trait<BigInt> for &BigInt {
+            type Output = BigInt;
+
+            fn $method(self, other: BigInt) -> BigInt {
+                self.$method(&other)
+            }
+        }
+
+        impl $trait<&BigInt> for BigInt {
+            type Output = BigInt;
+
+            fn $method(self, other: &BigInt) -> BigInt {
+                (&self).$method(other)
+            }
+        }
+
+        impl $trait<BigInt> for BigInt {
+            type Output = BigInt;
+
+            fn $method(self, other: BigInt) -> BigInt {
+                (&self).$method(&other)
+            }
+        }
+    };
+}
+
+impl_binop!(Add, add, arith::add);
+impl_binop!(Sub, sub, arith::subtract);
+impl_binop!(Mul, mul, arith::multiply);
+impl_binop!(Div, div, arith::quotient);
+impl_binop!(Rem, rem, arith::remainder);
+
+impl Ring for BigInt {
+    fn zero() -> BigInt {
+        BigInt::zero()
+    }
+
+    fn one() -> BigInt {
+        BigInt::one()
+    }
+
+    fn inverse(&self) -> Option<BigInt> {
+        if self.abs() == BigInt::one() {
+            Some(self.clone())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(big("00123").to_string(), "123");
+        assert_eq!(big("-0").to_string(), "0");
+        assert_eq!(big("+42").to_string(), "42");
+        assert_eq!(big("-007").to_string(), "-7");
+        assert_eq!("".parse::<BigInt>(), Err(ParseBigIntError::Empty));
+        assert_eq!("-".parse::<BigInt>(), Err(ParseBigIntError::Empty));
+        assert_eq!(
+            "12x4".parse::<BigInt>(),
+            Err(ParseBigIntError::InvalidDigit { index: 2, found: 'x' })
+        );
+        assert_eq!(
+            "--1".parse::<BigInt>(),
+            Err(ParseBigIntError::InvalidDigit { index: 1, found: '-' })
+        );
+    }
+
+    #[test]
+    fn test_display_flags() {
+        assert_eq!(format!("{:8}", big("-1234")), "   -1234");
+        assert_eq!(format!("{:<8}|", big("1234")), "1234    |");
+        assert_eq!(format!("{:*^9}", big("-1234")), "**-1234**");
+        assert_eq!(format!("{:+}", big("1234")), "+1234");
+        assert_eq!(format!("{:+}", big("0")), "+0");
+        assert_eq!(format!("{:08}", big("-1234")), "-0001234");
+        assert_eq!(format!("{:+08}", big("1234")), "+0001234");
+        assert_eq!(format!("{:2}", big("-1234")), "-1234");
+    }
+
+    #[test]
+    fn test_operators() {
+        let a = big("3141592653589793238462643383279502884197169399375105820974944592");
+        let b = big("2718281828459045235360287471352662497757247093699959574966967627");
+
+        assert_eq!(&a - &b, big("423310825130748003102355911926840386439922305675146246007976965"));
+        assert_eq!(&a + &b, big("5859874482048838473822930854632165381954416493075065395941912219"));
+        assert_eq!(&b - &a + &a, b);
+        assert_eq!(-(-a.clone()), a);
+        assert_eq!(big("-1234") * big("4321"), big("-5332114"));
+    }
+
+    #[test]
+    fn test_division() {
+        let a = big("3141592653589793238462643383279502884197169399375105820974944592");
+        let b = big("-2718281828459045235360287471352662497");
+
+        let (q, r) = a.div_rem(&b);
+
+        assert_eq!(&q * &b + &r, a);
+        assert!(r.abs() < b.abs());
+        assert_eq!(&a / &b, q);
+        assert_eq!(&a % &b, r);
+        assert_eq!(big("-7") / big("2"), big("-3"));
+        assert_eq!(big("-7") % big("2"), big("-1"));
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(big("2").pow(100), big("1267650600228229401496703205376"));
+        assert_eq!(big("-3").pow(3), big("-27"));
+        assert_eq!(BigInt::zero().pow(0), BigInt::one());
+        assert_eq!(big("10").pow(50).digit_count(), 51);
+    }
+
+    #[test]
+    fn test_canonical() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |n: &BigInt| {
+            let mut hasher = DefaultHasher::new();
+            n.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let a = big(&"9".repeat(300));
+        let zeros = [
+            big("-000"),
+            -BigInt::zero(),
+            &a - &a,
+            -&a + &a,
+            -&a * BigInt::zero(),
+            (-&a).mul_schoolbook(&BigInt::zero()),
+            (-&a).mul_iterative(&BigInt::zero()),
+            BigInt::zero() / -&a,
+            -&a % &a,
+            big("-0").abs(),
+        ];
+
+        for zero in &zeros {
+            println!("[test_canonical] {:?}", zero);
+
+            assert!(zero.is_canonical());
+            assert_eq!(*zero, BigInt::zero());
+            assert_eq!(hash(zero), hash(&BigInt::zero()));
+        }
+
+        assert!((-big("00120")).is_canonical());
+        assert_eq!(hash(&big("-00120")), hash(&big("-120")));
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(big("10") > big("9"));
+        assert!(big("-10") < big("-9"));
+        assert!(big("-1") < BigInt::zero());
+        assert_eq!(big("22").cmp(&big("22")), Ordering::Equal);
+        assert_eq!(big("-4").abs(), big("4"));
+        assert_eq!(big("-4").digit_count(), 1);
+    }
+}
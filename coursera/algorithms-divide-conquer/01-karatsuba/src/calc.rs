@@ -0,0 +1,569 @@
+//! the `calc` command: an interactive big integer calculator
+//!
+//! Lines are either an expression or `let name = expression`. Expressions use
+//! `+ - * / % ^`, unary minus, parentheses, numbers and variables, with `^`
+//! binding tightest and associating to the right, so `-2^2` is `-4`. `_` is
+//! the last result.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use karatsuba::limits::LimitExceeded;
+use karatsuba::BigInt;
+
+/// the syntax, printed by help at the prompt and by `karatsuba calc help`
+pub const HELP: &str = "\
+expressions: + - * / % ^, unary minus, parentheses, numbers and variables
+  let x = 2^127 - 1    bind a variable
+  _                    the last result
+  vars                 list the variables
+  history              list the previous results
+  quit                 leave";
+
+/// powers with more digits than this are refused rather than left to run
+/// for minutes; 2^332192 takes a few seconds
+const MAX_RESULT_DIGITS: usize = 100_000;
+
+const PROMPT: &str = "> ";
+
+/// an error and the span of the input it refers to, in byte offsets while
+/// parsing and in columns once returned
+#[derive(Debug, PartialEq)]
+pub struct CalcError {
+    pub start: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+impl CalcError {
+    fn new(start: usize, end: usize, message: String) -> CalcError {
+        CalcError { start, end, message }
+    }
+
+    /// turn byte offsets into `line` into columns, so carets line up after non-ASCII input
+    fn in_columns(self, line: &str) -> CalcError {
+        let column = |i: usize| line[..i].chars().count();
+
+        CalcError::new(column(self.start), column(self.end), self.message)
+    }
+
+    /// a line of carets under the span, for input echoed after `indent` columns
+    pub fn caret(&self, indent: usize) -> String {
+        let width = std::cmp::max(self.end - self.start, 1);
+
+        format!("{}{} {}", " ".repeat(indent + self.start), "^".repeat(width), self.message)
+    }
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.start + 1)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(String),
+    Name(String),
+    Op(char),
+    End,
+}
+
+/// a token and the byte range it came from
+#[derive(Clone, Debug)]
+struct Spanned {
+    token: Token,
+    start: usize,
+    end: usize,
+}
+
+fn tokenize(line: &str) -> Result<Vec<Spanned>, CalcError> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let token = if c.is_whitespace() {
+            continue;
+        } else if c.is_ascii_digit() || c.is_alphabetic() || c == '_' {
+            let mut end = start + c.len_utf8();
+
+            while let Some(&(i, next)) = chars.peek() {
+                if !(next.is_ascii_digit() || next.is_alphabetic() || next == '_') {
+                    break;
+                }
+
+                end = i + next.len_utf8();
+                chars.next();
+            }
+
+            let word = &line[start..end];
+
+            if c.is_ascii_digit() {
+                if let Some(i) = word.find(|c: char| !c.is_ascii_digit()) {
+                    return Err(CalcError::new(start + i, end, String::from("invalid number")));
+                }
+
+                Token::Number(String::from(word))
+            } else {
+                Token::Name(String::from(word))
+            }
+        } else if "+-*/%^()=".contains(c) {
+            Token::Op(c)
+        } else {
+            return Err(CalcError::new(start, start + c.len_utf8(), format!("unexpected character {:?}", c)));
+        };
+
+        let end = chars.peek().map_or(line.len(), |&(i, _)| i);
+
+        tokens.push(Spanned { token, start, end });
+    }
+
+    tokens.push(Spanned {
+        token: Token::End,
+        start: line.len(),
+        end: line.len(),
+    });
+
+    Ok(tokens)
+}
+
+/// a value and the span of the expression it came from
+struct Value {
+    value: BigInt,
+    start: usize,
+    end: usize,
+}
+
+/// recursive descent over one line, evaluating as it goes
+struct Parser<'a> {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    vars: &'a HashMap<String, BigInt>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> &Spanned {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Spanned {
+        let spanned = self.tokens[self.pos].clone();
+
+        if spanned.token != Token::End {
+            self.pos += 1;
+        }
+
+        spanned
+    }
+
+    fn peek_op(&self, ops: &str) -> Option<char> {
+        match self.peek().token {
+            Token::Op(op) if ops.contains(op) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> CalcError {
+        let spanned = self.peek();
+
+        let found = match &spanned.token {
+            Token::End => String::from("end of input"),
+            Token::Number(n) => n.clone(),
+            Token::Name(name) => name.clone(),
+            Token::Op(op) => op.to_string(),
+        };
+
+        CalcError::new(spanned.start, spanned.end, format!("expected {}, found {}", expected, found))
+    }
+
+    /// expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Value, CalcError> {
+        let mut left = self.term()?;
+
+        while let Some(op) = self.peek_op("+-") {
+            self.next();
+
+            let right = self.term()?;
+
+            left = Value {
+                value: if op == '+' { &left.value + &right.value } else { &left.value - &right.value },
+                start: left.start,
+                end: right.end,
+            };
+        }
+
+        Ok(left)
+    }
+
+    /// term := unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<Value, CalcError> {
+        let mut left = self.unary()?;
+
+        while let Some(op) = self.peek_op("*/%") {
+            self.next();
+
+            let right = self.unary()?;
+
+            if op != '*' && right.value.is_zero() {
+                return Err(CalcError::new(right.start, right.end, String::from("division by zero")));
+            }
+
+            let value = match op {
+                '*' => &left.value * &right.value,
+                '/' => &left.value / &right.value,
+                _ => &left.value % &right.value,
+            };
+
+            left = Value {
+                value,
+                start: left.start,
+                end: right.end,
+            };
+        }
+
+        Ok(left)
+    }
+
+    /// unary := '-' unary | power
+    fn unary(&mut self) -> Result<Value, CalcError> {
+        if self.peek_op("-").is_some() {
+            let start = self.next().start;
+            let operand = self.unary()?;
+
+            return Ok(Value {
+                value: -operand.value,
+                start,
+                end: operand.end,
+            });
+        }
+
+        self.power()
+    }
+
+    /// power := atom ('^' unary)?
+    fn power(&mut self) -> Result<Value, CalcError> {
+        let base = self.atom()?;
+
+        if self.peek_op("^").is_none() {
+            return Ok(base);
+        }
+
+        self.next();
+
+        let exponent = self.unary()?;
+
+        if exponent.value.is_negative() {
+            return Err(CalcError::new(exponent.start, exponent.end, String::from("negative exponent")));
+        }
+
+        let exp = if base.value.abs() <= BigInt::one() && !exponent.value.is_zero() {
+            // 0, 1 and -1 only care whether the exponent is odd
+            if (&exponent.value % BigInt::from(2)).is_zero() { 2 } else { 1 }
+        } else {
+            u32::try_from(&exponent.value)
+                .ok()
+                .ok_or_else(|| CalcError::new(exponent.start, exponent.end, String::from("exponent too large")))?
+        };
+
+        let digits = power_digits(&base.value, exp);
+
+        if digits > MAX_RESULT_DIGITS as f64 {
+            let error = LimitExceeded::ResultDigits {
+                digits: digits as usize,
+                max: MAX_RESULT_DIGITS,
+            };
+
+            return Err(CalcError::new(base.start, exponent.end, error.to_string()));
+        }
+
+        Ok(Value {
+            value: base.value.pow(exp),
+            start: base.start,
+            end: exponent.end,
+        })
+    }
+
+    /// atom := number | name | '(' expr ')'
+    fn atom(&mut self) -> Result<Value, CalcError> {
+        let spanned = self.peek().clone();
+
+        let value = match &spanned.token {
+            Token::Number(n) => n.parse().unwrap(),
+            Token::Name(name) => match self.vars.get(name) {
+                Some(value) => value.clone(),
+                None => return Err(CalcError::new(spanned.start, spanned.end, format!("unknown variable {}", name))),
+            },
+            Token::Op('(') => {
+                self.next();
+
+                let inner = self.expr()?;
+
+                if self.peek_op(")").is_none() {
+                    return Err(self.unexpected("')'"));
+                }
+
+                let end = self.next().end;
+
+                return Ok(Value {
+                    value: inner.value,
+                    start: spanned.start,
+                    end,
+                });
+            }
+            _ => return Err(self.unexpected("a number, variable or '('")),
+        };
+
+        self.next();
+
+        Ok(Value {
+            value,
+            start: spanned.start,
+            end: spanned.end,
+        })
+    }
+}
+
+/// about how many digits base^exp has, from log10 of the base
+fn power_digits(base: &BigInt, exp: u32) -> f64 {
+    let log10 = base.abs().to_f64().log10();
+
+    // bases too large for an f64 have at least as many digits as that many
+    let log10 = if log10.is_finite() { log10 } else { base.digit_count() as f64 };
+
+    (exp as f64 * log10).floor() + 1.0
+}
+
+/// the calculator state: variables and the results so far
+#[derive(Default)]
+pub struct Calc {
+    vars: HashMap<String, BigInt>,
+    history: Vec<(String, BigInt)>,
+}
+
+impl Calc {
+    /// evaluate a line, returning what to print, if anything
+    pub fn eval_line(&mut self, line: &str) -> Result<Option<String>, CalcError> {
+        self.eval(line).map_err(|e| e.in_columns(line))
+    }
+
+    /// evaluate a line, with errors spanning byte offsets
+    fn eval(&mut self, line: &str) -> Result<Option<String>, CalcError> {
+        let mut tokens = tokenize(line)?;
+
+        let binding = match (&tokens[0].token, tokens.get(1).map(|t| &t.token)) {
+            (Token::Name(word), Some(Token::Name(name))) if word == "let" => {
+                let name = name.clone();
+
+                match tokens.get(2) {
+                    Some(Spanned { token: Token::Op('='), .. }) => {}
+                    Some(spanned) => {
+                        return Err(CalcError::new(spanned.start, spanned.end, String::from("expected '='")));
+                    }
+                    None => unreachable!(),
+                }
+
+                if name == "let" || name == "_" {
+                    return Err(CalcError::new(tokens[1].start, tokens[1].end, format!("cannot bind {}", name)));
+                }
+
+                tokens.drain(..3);
+
+                Some(name)
+            }
+            (Token::Name(word), _) if word == "let" => {
+                return Err(CalcError::new(tokens[1].start, tokens[1].end, String::from("expected a variable name")));
+            }
+            _ => None,
+        };
+
+        if binding.is_none() && tokens.len() == 1 {
+            return Ok(None);
+        }
+
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            vars: &self.vars,
+        };
+
+        let value = parser.expr()?.value;
+
+        if parser.peek().token != Token::End {
+            return Err(parser.unexpected("an operator"));
+        }
+
+        self.vars.insert(String::from("_"), value.clone());
+        self.history.push((String::from(line.trim()), value.clone()));
+
+        Ok(Some(match binding {
+            Some(name) => {
+                let output = format!("{} = {}", name, value);
+                self.vars.insert(name, value);
+                output
+            }
+            None => value.to_string(),
+        }))
+    }
+
+    /// the variables, sorted by name
+    fn vars(&self) -> String {
+        let mut names: Vec<&String> = self.vars.keys().filter(|name| *name != "_").collect();
+        names.sort();
+
+        names
+            .iter()
+            .map(|name| format!("{} = {}\n", name, self.vars[*name]))
+            .collect()
+    }
+
+    /// the lines evaluated so far, numbered, with their results
+    fn history(&self) -> String {
+        self.history
+            .iter()
+            .enumerate()
+            .map(|(i, (line, value))| format!("{:>4}  {}  => {}\n", i + 1, line, value))
+            .collect()
+    }
+}
+
+/// evaluate a single expression given on the command line
+pub fn eval(expression: &str) -> Result<String, CalcError> {
+    Ok(Calc::default().eval_line(expression)?.unwrap_or_default())
+}
+
+/// read lines from stdin until end of input or `quit`, prompting if stdin is a terminal
+pub fn repl() -> io::Result<()> {
+    let interactive = io::stdin().is_terminal();
+
+    let mut calc = Calc::default();
+    let mut stdout = io::stdout();
+
+    let prompt = |stdout: &mut io::Stdout| {
+        if interactive {
+            write!(stdout, "{}", PROMPT)?;
+            stdout.flush()?;
+        }
+
+        Ok::<(), io::Error>(())
+    };
+
+    prompt(&mut stdout)?;
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+
+        match line.trim() {
+            "quit" | "exit" => break,
+            "help" => writeln!(stdout, "{}", HELP)?,
+            "vars" => write!(stdout, "{}", calc.vars())?,
+            "history" => write!(stdout, "{}", calc.history())?,
+            _ => match calc.eval_line(&line) {
+                Ok(Some(output)) => writeln!(stdout, "{}", output)?,
+                Ok(None) => {}
+                Err(e) if interactive => writeln!(stdout, "{}", e.caret(PROMPT.len()))?,
+                Err(e) => writeln!(stdout, "{}\n{}", line, e.caret(0))?,
+            },
+        }
+
+        prompt(&mut stdout)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_eval_test(line: &str, expected: &str) {
+        let result = eval(line).unwrap();
+
+        println!("[run_eval_test] {} = {}", line, result);
+
+        assert_eq!(result, expected);
+    }
+
+    fn run_error_test(line: &str, caret: &str) {
+        let error = eval(line).unwrap_err();
+
+        println!("[run_error_test] {}\n{}", line, error.caret(0));
+
+        assert_eq!(error.caret(0), caret);
+    }
+
+    #[test]
+    fn test_eval() {
+        run_eval_test("1 + 2 * 3", "7");
+        run_eval_test("(1 + 2) * 3", "9");
+        run_eval_test("2^521 - 1", &(BigInt::from(2).pow(521) - BigInt::one()).to_string());
+        run_eval_test("2^3^2", "512");
+        run_eval_test("-2^2", "-4");
+        run_eval_test("(-2)^2", "4");
+        run_eval_test("--5", "5");
+        run_eval_test("2 * -3", "-6");
+        run_eval_test("-7 / 2", "-3");
+        run_eval_test("-7 % 2", "-1");
+        run_eval_test("100 - 10 - 1", "89");
+        run_eval_test("1^99999999999", "1");
+        run_eval_test("(-10)^3 * 10^5", "-100000000");
+        run_eval_test("(-1)^99999999999", "-1");
+        run_eval_test("0^0", "1");
+        run_eval_test("   ", "");
+    }
+
+    #[test]
+    fn test_power_digits() {
+        // the largest powers of 10 and 2 under the cap, and the smallest over it
+        assert_eq!(power_digits(&BigInt::from(10), 99999), 100000.0);
+        assert_eq!(power_digits(&BigInt::from(10), 100000), 100001.0);
+        assert_eq!(power_digits(&BigInt::from(-2), 332192), 100000.0);
+        assert_eq!(power_digits(&BigInt::from(2), 1000), BigInt::from(2).pow(1000).digit_count() as f64);
+
+        let huge: BigInt = "9".repeat(400).parse().unwrap();
+
+        assert_eq!(power_digits(&huge, 0), 1.0);
+        assert!(power_digits(&huge, 250) > MAX_RESULT_DIGITS as f64);
+    }
+
+    #[test]
+    fn test_bindings() {
+        let mut calc = Calc::default();
+
+        assert_eq!(calc.eval_line("let x = 2^64"), Ok(Some(String::from("x = 18446744073709551616"))));
+        assert_eq!(calc.eval_line("x * 3 + x"), Ok(Some(String::from("73786976294838206464"))));
+        assert_eq!(calc.eval_line("_ / x"), Ok(Some(String::from("4"))));
+        assert_eq!(calc.eval_line("let y_2 = x - _"), Ok(Some(String::from("y_2 = 18446744073709551612"))));
+        assert_eq!(calc.vars(), "x = 18446744073709551616\ny_2 = 18446744073709551612\n");
+        assert!(calc.history().starts_with("   1  let x = 2^64  => 18446744073709551616\n"));
+        assert_eq!(calc.history().lines().count(), 4);
+    }
+
+    #[test]
+    fn test_errors() {
+        run_error_test("1 + * 2", "    ^ expected a number, variable or '(', found *");
+        run_error_test("(1 + 2", "      ^ expected ')', found end of input");
+        run_error_test("3 $ 4", "  ^ unexpected character '$'");
+        run_error_test("2 3", "  ^ expected an operator, found 3");
+        run_error_test("1 / (2 - 2)", "    ^^^^^^^ division by zero");
+        run_error_test("x + 1", "^ unknown variable x");
+        run_error_test("2 ^ -1", "    ^^ negative exponent");
+        run_error_test("10^99999999999", "   ^^^^^^^^^^^ exponent too large");
+        run_error_test("1 + 10^100000", "    ^^^^^^^^^ result could have 100001 digits, more than the limit of 100000");
+        run_error_test("(-7)^200000", "^^^^^^^^^^^ result could have 169020 digits, more than the limit of 100000");
+        run_error_test("«1» + 2", "^ unexpected character '«'");
+        run_error_test("π + 2 * ü", "^ unknown variable π");
+
+        // carets count characters, not bytes
+        let mut calc = Calc::default();
+
+        calc.eval_line("let ü = 5").unwrap();
+
+        assert_eq!(calc.eval_line("ü + ü / 0").unwrap_err().caret(0), "        ^ division by zero");
+        run_error_test("12ab", "  ^^ invalid number");
+        run_error_test("let 5 = 1", "    ^ expected a variable name");
+        run_error_test("let x 1", "      ^ expected '='");
+        run_error_test("let _ = 1", "    ^ cannot bind _");
+    }
+}
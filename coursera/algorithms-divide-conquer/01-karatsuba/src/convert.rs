@@ -0,0 +1,328 @@
+//! Conversions between [`BigInt`] and the primitive number types and byte strings.
+
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::arith;
+use crate::BigInt;
+
+/// The error returned when a [`BigInt`] does not fit in the target primitive type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TryFromBigIntError(());
+
+impl fmt::Display for TryFromBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "out of range integral type conversion attempted")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryFromBigIntError {}
+
+/// The error returned when converting a non-finite float to a [`BigInt`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TryFromFloatError {
+    /// The value was NaN.
+    NaN,
+    /// The value was positive or negative infinity.
+    Infinite,
+}
+
+impl fmt::Display for TryFromFloatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TryFromFloatError::NaN => write!(f, "cannot convert NaN to an integer"),
+            TryFromFloatError::Infinite => write!(f, "cannot convert infinity to an integer"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryFromFloatError {}
+
+macro_rules! impl_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for BigInt {
+                fn from(n: $t) -> BigInt {
+                    BigInt::from_digits(n.to_string().chars().collect())
+                }
+            }
+
+            impl TryFrom<&BigInt> for $t {
+                type Error = TryFromBigIntError;
+
+                /// the digits are already valid, so parsing can only fail on overflow
+                fn try_from(n: &BigInt) -> Result<$t, TryFromBigIntError> {
+                    n.to_string().parse().map_err(|_| TryFromBigIntError(()))
+                }
+            }
+
+            impl TryFrom<BigInt> for $t {
+                type Error = TryFromBigIntError;
+
+                fn try_from(n: BigInt) -> Result<$t, TryFromBigIntError> {
+                    <$t>::try_from(&n)
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl BigInt {
+    /// Convert to the nearest `f64`, rounding ties to even.
+    ///
+    /// Values too large for `f64` become positive or negative infinity.
+    pub fn to_f64(&self) -> f64 {
+        // float parsing in core is correctly rounded
+        self.to_string().parse().unwrap()
+    }
+
+    /// Convert to the nearest `f32`, rounding ties to even.
+    ///
+    /// Values too large for `f32` become positive or negative infinity.
+    pub fn to_f32(&self) -> f32 {
+        self.to_string().parse().unwrap()
+    }
+
+    /// Convert a finite `f64`, truncating any fractional part toward zero.
+    pub fn from_f64(x: f64) -> Result<BigInt, TryFromFloatError> {
+        if x.is_nan() {
+            return Err(TryFromFloatError::NaN);
+        }
+
+        if x.is_infinite() {
+            return Err(TryFromFloatError::Infinite);
+        }
+
+        let bits = x.to_bits();
+
+        let negative = bits >> 63 == 1;
+        let biased_exponent = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = bits & ((1 << 52) - 1);
+
+        // x = mantissa * 2^exponent
+        let (mantissa, exponent) = if biased_exponent == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | (1 << 52), biased_exponent - 1075)
+        };
+
+        let magnitude = if exponent >= 0 {
+            let mut digits: Vec<char> = mantissa.to_string().chars().collect();
+
+            let mut remaining = exponent;
+
+            while remaining > 0 {
+                let step = core::cmp::min(remaining, 16);
+
+                digits = arith::multiply_small(&digits, 1 << step, 0);
+                remaining -= step;
+            }
+
+            BigInt::from_digits(digits)
+        } else if exponent > -64 {
+            BigInt::from(mantissa >> -exponent)
+        } else {
+            BigInt::zero()
+        };
+
+        Ok(if negative { -magnitude } else { magnitude })
+    }
+
+    /// Convert a finite `f32`, truncating any fractional part toward zero.
+    pub fn from_f32(x: f32) -> Result<BigInt, TryFromFloatError> {
+        BigInt::from_f64(x as f64)
+    }
+
+    /// The absolute value as big-endian bytes, with no leading zero bytes.
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = self.to_bytes_le();
+        bytes.reverse();
+        bytes
+    }
+
+    /// The absolute value as little-endian bytes, with no trailing zero bytes.
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        let mut digits = arith::abs(self.digits());
+
+        let mut bytes = Vec::new();
+
+        loop {
+            let (quotient, byte) = arith::divide_small(&digits, 256);
+
+            bytes.push(byte as u8);
+            digits = quotient;
+
+            if digits == ['0'] {
+                break;
+            }
+        }
+
+        bytes
+    }
+
+    /// Interpret big-endian bytes as a non-negative integer.
+    pub fn from_bytes_be(bytes: &[u8]) -> BigInt {
+        let mut digits = vec!['0'];
+
+        for &byte in bytes {
+            digits = arith::multiply_small(&digits, 256, byte as u32);
+        }
+
+        BigInt::from_digits(digits)
+    }
+
+    /// Interpret little-endian bytes as a non-negative integer.
+    pub fn from_bytes_le(bytes: &[u8]) -> BigInt {
+        let mut be = bytes.to_vec();
+        be.reverse();
+        BigInt::from_bytes_be(&be)
+    }
+
+    /// The shortest big-endian two's complement representation.
+    pub fn to_signed_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = self.to_signed_bytes_le();
+        bytes.reverse();
+        bytes
+    }
+
+    /// The shortest little-endian two's complement representation.
+    pub fn to_signed_bytes_le(&self) -> Vec<u8> {
+        if !self.is_negative() {
+            let mut bytes = self.to_bytes_le();
+
+            if bytes.last().unwrap() & 0x80 != 0 {
+                bytes.push(0);
+            }
+
+            return bytes;
+        }
+
+        // -m in two's complement is the bitwise complement of m - 1
+        let mut bytes = (-self - BigInt::one()).to_bytes_le();
+
+        for byte in bytes.iter_mut() {
+            *byte = !*byte;
+        }
+
+        if bytes.last().unwrap() & 0x80 == 0 {
+            bytes.push(0xff);
+        }
+
+        bytes
+    }
+
+    /// Interpret big-endian two's complement bytes.
+    pub fn from_signed_bytes_be(bytes: &[u8]) -> BigInt {
+        let mut le = bytes.to_vec();
+        le.reverse();
+        BigInt::from_signed_bytes_le(&le)
+    }
+
+    /// Interpret little-endian two's complement bytes.
+    pub fn from_signed_bytes_le(bytes: &[u8]) -> BigInt {
+        match bytes.last() {
+            Some(&top) if top & 0x80 != 0 => {
+                let complement: Vec<u8> = bytes.iter().map(|byte| !byte).collect();
+
+                -(BigInt::from_bytes_le(&complement) + BigInt::one())
+            }
+            _ => BigInt::from_bytes_le(bytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_round_trip_test(n: i128) {
+        let big = BigInt::from(n);
+
+        println!("[run_round_trip_test] {}", n);
+
+        assert_eq!(big.to_string(), n.to_string());
+        assert_eq!(i128::try_from(&big), Ok(n));
+        assert_eq!(BigInt::from_signed_bytes_le(&big.to_signed_bytes_le()), big);
+        assert_eq!(BigInt::from_signed_bytes_be(&big.to_signed_bytes_be()), big);
+        assert_eq!(BigInt::from_bytes_be(&big.to_bytes_be()), big.abs());
+        assert_eq!(big.to_f64(), n as f64);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        run_round_trip_test(0);
+        run_round_trip_test(1);
+        run_round_trip_test(-1);
+        run_round_trip_test(127);
+        run_round_trip_test(-128);
+        run_round_trip_test(-129);
+        run_round_trip_test(9007199254740993);
+        run_round_trip_test(i128::MAX);
+        run_round_trip_test(i128::MIN);
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(u8::try_from(BigInt::from(255u8)), Ok(255));
+        assert_eq!(u8::try_from(BigInt::from(256)), Err(TryFromBigIntError(())));
+        assert_eq!(u64::try_from(BigInt::from(-1)), Err(TryFromBigIntError(())));
+        assert_eq!(i8::try_from(BigInt::from(-128)), Ok(-128));
+        assert_eq!(u128::try_from(BigInt::from(u128::MAX)), Ok(u128::MAX));
+        assert!(u128::try_from(BigInt::from(u128::MAX) + BigInt::one()).is_err());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let n = BigInt::from(0x0102_0304_0506u64);
+
+        assert_eq!(n.to_bytes_be(), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(n.to_bytes_le(), vec![6, 5, 4, 3, 2, 1]);
+        assert_eq!(BigInt::zero().to_bytes_be(), vec![0]);
+        assert_eq!(BigInt::from_bytes_le(&[]), BigInt::zero());
+        assert_eq!(BigInt::from_bytes_be(&[0, 0, 1, 0]), BigInt::from(256));
+
+        assert_eq!(BigInt::from(128).to_signed_bytes_be(), vec![0, 0x80]);
+        assert_eq!(BigInt::from(-128).to_signed_bytes_be(), vec![0x80]);
+        assert_eq!(BigInt::from(-129).to_signed_bytes_be(), vec![0xff, 0x7f]);
+        assert_eq!(BigInt::from(-1).to_signed_bytes_le(), vec![0xff]);
+        assert_eq!(BigInt::from_signed_bytes_be(&[0xff, 0xff, 0xfe]), BigInt::from(-2));
+    }
+
+    #[test]
+    fn test_to_float() {
+        // 2^53 + 1 is a tie between 2^53 and 2^53 + 2, which rounds to even
+        assert_eq!(BigInt::from(9007199254740993u64).to_f64(), 9007199254740992.0);
+        assert_eq!(BigInt::from(9007199254740995u64).to_f64(), 9007199254740996.0);
+        assert_eq!(BigInt::from(-16777217).to_f32(), -16777216.0);
+
+        let huge: BigInt = "1".repeat(400).parse().unwrap();
+
+        assert_eq!(huge.to_f64(), f64::INFINITY);
+        assert_eq!((-huge).to_f64(), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_from_float() {
+        assert_eq!(BigInt::from_f64(f64::NAN), Err(TryFromFloatError::NaN));
+        assert_eq!(BigInt::from_f64(f64::NEG_INFINITY), Err(TryFromFloatError::Infinite));
+        assert_eq!(BigInt::from_f64(-0.0), Ok(BigInt::zero()));
+        assert_eq!(BigInt::from_f64(2.9), Ok(BigInt::from(2)));
+        assert_eq!(BigInt::from_f64(-2.9), Ok(BigInt::from(-2)));
+        assert_eq!(BigInt::from_f64(5e-324), Ok(BigInt::zero()));
+        assert_eq!(BigInt::from_f64(1e20), Ok(BigInt::from(100000000000000000000u128)));
+        assert_eq!(BigInt::from_f32(16777216.0), Ok(BigInt::from(16777216)));
+
+        let max = BigInt::from_f64(f64::MAX).unwrap();
+
+        assert_eq!(max.digit_count(), 309);
+        assert_eq!(max.to_f64(), f64::MAX);
+    }
+}
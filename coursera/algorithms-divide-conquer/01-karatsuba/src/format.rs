@@ -0,0 +1,274 @@
+//! Formatting huge numbers for people to read.
+//!
+//! `Display` writes every digit. [`BigInt::format_with`] can group the digits,
+//! switch to scientific notation or leave out the middle of a number:
+//!
+//! ```
+//! use karatsuba::format::FormatOptions;
+//! use karatsuba::BigInt;
+//!
+//! let e: BigInt = "2718281828459045235360287471352662497757247093699959574966967627".parse().unwrap();
+//!
+//! let mut options = FormatOptions::default();
+//! options.scientific = Some(6);
+//!
+//! assert_eq!(e.format_with(&options), "2.71828e63");
+//!
+//! let mut options = FormatOptions::default();
+//! options.truncate = Some(5);
+//!
+//! assert_eq!(e.format_with(&options), "27182…67627 (64 digits)");
+//!
+//! let mut options = FormatOptions::default();
+//! options.separator = Some(',');
+//!
+//! assert_eq!(BigInt::from(-1234567).format_with(&options), "-1,234,567");
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::ops::Range;
+
+use crate::BigInt;
+
+/// The most significant digits [`FormatOptions::scientific`] can ask for.
+pub const MAX_SIGNIFICANT_DIGITS: usize = 1 << 20;
+
+/// How [`BigInt::format_with`] writes a number.
+///
+/// The default writes every digit, like `Display`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FormatOptions {
+    /// Put this between groups of digits, e.g. `Some(',')` for thousands
+    /// separators. Not used in scientific notation.
+    pub separator: Option<char>,
+    /// The digits in each group, counted from the right. Defaults to 3.
+    pub group_size: usize,
+    /// Write `d.ddde<exponent>` with this many significant digits, rounding
+    /// half away from zero, e.g. `2.71828e63`. Numbers with fewer digits are
+    /// padded with zeros. At most [`MAX_SIGNIFICANT_DIGITS`].
+    pub scientific: Option<usize>,
+    /// Write numbers with more than twice this many digits as this many
+    /// digits from each end and the digit count, e.g. `31415…44592 (64 digits)`.
+    pub truncate: Option<usize>,
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions {
+            separator: None,
+            group_size: 3,
+            scientific: None,
+            truncate: None,
+        }
+    }
+}
+
+/// write the digits in `range`, with separators where they fall in the whole number
+fn grouped(text: &mut String, digits: &[char], range: Range<usize>, options: &FormatOptions) {
+    let start = range.start;
+
+    for i in range {
+        if let Some(separator) = options.separator {
+            if i > start && options.group_size > 0 && (digits.len() - i).is_multiple_of(options.group_size) {
+                text.push(separator);
+            }
+        }
+
+        text.push(digits[i]);
+    }
+}
+
+/// write the digits rounded to `significant` of them, in scientific notation
+fn scientific(text: &mut String, digits: &[char], significant: usize) {
+    let mut mantissa: Vec<u32> = digits.iter().take(significant).map(|d| d.to_digit(10).unwrap()).collect();
+    let mut exponent = digits.len() - 1;
+
+    // the first digit dropped decides the rounding
+    if digits.get(significant).is_some_and(|&d| d >= '5') {
+        let mut i = mantissa.len();
+
+        loop {
+            if i == 0 {
+                // all nines rounded up to the next power of ten
+                mantissa.insert(0, 1);
+                mantissa.pop();
+                exponent += 1;
+                break;
+            }
+
+            i -= 1;
+
+            if mantissa[i] == 9 {
+                mantissa[i] = 0;
+            } else {
+                mantissa[i] += 1;
+                break;
+            }
+        }
+    }
+
+    // numbers shorter than the significant digits are padded with zeros
+    mantissa.resize(significant, 0);
+
+    for (i, d) in mantissa.iter().enumerate() {
+        if i == 1 {
+            text.push('.');
+        }
+
+        text.push(core::char::from_digit(*d, 10).unwrap());
+    }
+
+    write!(text, "e{}", exponent).unwrap();
+}
+
+impl BigInt {
+    /// Format for reading rather than parsing back, as set out by `options`.
+    ///
+    /// Scientific notation takes precedence over truncation. The result can
+    /// be padded and aligned with the usual `format!` flags.
+    ///
+    /// # Panics
+    ///
+    /// If `options.scientific` asks for more than [`MAX_SIGNIFICANT_DIGITS`].
+    pub fn format_with(&self, options: &FormatOptions) -> String {
+        let negative = self.is_negative();
+        let digits = &self.digits()[negative as usize..];
+
+        let mut text = String::new();
+
+        if negative {
+            text.push('-');
+        }
+
+        if let Some(significant) = options.scientific {
+            assert!(
+                significant <= MAX_SIGNIFICANT_DIGITS,
+                "{} significant digits is more than the {} allowed",
+                significant,
+                MAX_SIGNIFICANT_DIGITS
+            );
+
+            scientific(&mut text, digits, significant.max(1));
+        } else if let Some(keep) = options.truncate.filter(|&keep| digits.len() > 2 * keep) {
+            grouped(&mut text, digits, 0..keep, options);
+            text.push('…');
+            grouped(&mut text, digits, digits.len() - keep..digits.len(), options);
+
+            write!(text, " ({} digits)", digits.len()).unwrap();
+        } else {
+            grouped(&mut text, digits, 0..digits.len(), options);
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PI: &str = "3141592653589793238462643383279502884197169399375105820974944592";
+
+    fn run_format_test(n: &str, options: &FormatOptions, expected: &str) {
+        let text = n.parse::<BigInt>().unwrap().format_with(options);
+
+        println!("[run_format_test] {} -> {}", n, text);
+
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn test_default() {
+        let options = FormatOptions::default();
+
+        run_format_test(PI, &options, PI);
+        run_format_test("-0012", &options, "-12");
+        run_format_test("0", &options, "0");
+    }
+
+    #[test]
+    fn test_grouping() {
+        let mut options = FormatOptions {
+            separator: Some(','),
+            ..FormatOptions::default()
+        };
+
+        run_format_test("1234567", &options, "1,234,567");
+        run_format_test("-123456", &options, "-123,456");
+        run_format_test("999", &options, "999");
+        run_format_test("1000", &options, "1,000");
+
+        options.separator = Some('_');
+        options.group_size = 4;
+
+        run_format_test("123456789", &options, "1_2345_6789");
+
+        options.group_size = 0;
+
+        run_format_test("123456789", &options, "123456789");
+    }
+
+    #[test]
+    fn test_scientific() {
+        let mut options = FormatOptions {
+            scientific: Some(6),
+            ..FormatOptions::default()
+        };
+
+        run_format_test(PI, &options, "3.14159e63");
+        run_format_test("-271828", &options, "-2.71828e5");
+        run_format_test("42", &options, "4.20000e1");
+        run_format_test("0", &options, "0.00000e0");
+
+        options.scientific = Some(3);
+
+        run_format_test("12345", &options, "1.23e4");
+        run_format_test("12350", &options, "1.24e4");
+        run_format_test("9995", &options, "1.00e4");
+        run_format_test("-99999", &options, "-1.00e5");
+
+        options.scientific = Some(1);
+
+        run_format_test("86", &options, "9e1");
+
+        // scientific notation wins, and has no separators
+        options.separator = Some(',');
+        options.truncate = Some(2);
+
+        run_format_test("1234567", &options, "1e6");
+
+        options.scientific = Some(MAX_SIGNIFICANT_DIGITS);
+
+        assert_eq!(BigInt::from(7).format_with(&options).len(), MAX_SIGNIFICANT_DIGITS + 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "significant digits is more than the 1048576 allowed")]
+    fn test_scientific_limit() {
+        let options = FormatOptions {
+            scientific: Some(usize::MAX),
+            ..FormatOptions::default()
+        };
+
+        BigInt::from(7).format_with(&options);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut options = FormatOptions {
+            truncate: Some(5),
+            ..FormatOptions::default()
+        };
+
+        run_format_test(PI, &options, "31415…44592 (64 digits)");
+        run_format_test("-12345678901", &options, "-12345…78901 (11 digits)");
+        run_format_test("1234567890", &options, "1234567890");
+
+        options.separator = Some(',');
+
+        run_format_test(PI, &options, "3,141,5…44,592 (64 digits)");
+    }
+}
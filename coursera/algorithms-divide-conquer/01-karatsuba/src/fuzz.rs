@@ -0,0 +1,345 @@
+//! Differential and property testing of the digit arithmetic.
+//!
+//! [`run`] feeds random operands to the addition, subtraction, multiplication
+//! and comparison routines behind [`BigInt`](crate::BigInt), checking them
+//! against `i128` arithmetic, against schoolbook multiplication, and against
+//! algebraic identities. Operands have random signs and lengths, leading
+//! zeros, and edge values such as `0`, `-0`, `10^k` and `10^k - 1`.
+//!
+//! A failing case is shrunk to a small one before it is reported:
+//!
+//! ```
+//! let outcome = karatsuba::fuzz::run(2024, 50);
+//!
+//! assert!(outcome.is_ok(), "{}", outcome.unwrap_err());
+//! ```
+//!
+//! `karatsuba fuzz` runs the same checks from the command line.
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::string::String;
+use std::vec::Vec;
+
+use crate::arith::{self, v2s};
+use crate::random::Rng;
+
+/// Digits, with a leading `'-'` if negative, exactly as passed in.
+type Operand = Vec<char>;
+
+/// a property: its name, how many operands it takes and the check itself
+type Property = (&'static str, usize, fn(&[Operand]) -> Result<(), String>);
+
+const PROPERTIES: &[Property] = &[
+    ("add matches i128", 2, |x| against_i128(x, i128::checked_add, arith::add)),
+    ("subtract matches i128", 2, |x| against_i128(x, i128::checked_sub, arith::subtract)),
+    ("multiply matches i128", 2, |x| against_i128(x, i128::checked_mul, arith::multiply)),
+    ("larger matches i128", 2, larger_matches_i128),
+    ("multiply matches schoolbook", 2, |x| {
+        same("multiply", &arith::multiply(&x[0], &x[1]), &arith::multiply_schoolbook(&x[0], &x[1]))
+    }),
+    ("multiply matches the allocating recursion", 2, |x| {
+        let recursive = arith::multiply_probed(&x[0], &x[1], &mut arith::Probe::default());
+
+        same("multiply", &arith::multiply(&x[0], &x[1]), &recursive)
+    }),
+    ("add commutes", 2, |x| same("a + b", &arith::add(&x[0], &x[1]), &arith::add(&x[1], &x[0]))),
+    ("multiply commutes", 2, |x| {
+        same("a * b", &arith::multiply(&x[0], &x[1]), &arith::multiply(&x[1], &x[0]))
+    }),
+    ("a - b + b = a", 2, |x| {
+        same("a - b + b", &arith::add(&arith::subtract(&x[0], &x[1]), &x[1]), &x[0])
+    }),
+    ("a * (b + c) = a * b + a * c", 3, |x| {
+        let left = arith::multiply(&x[0], &arith::add(&x[1], &x[2]));
+        let right = arith::add(&arith::multiply(&x[0], &x[1]), &arith::multiply(&x[0], &x[2]));
+
+        same("a * (b + c)", &left, &right)
+    }),
+    ("results are canonical", 2, |x| {
+        let results = [
+            ("a + b", arith::add(&x[0], &x[1])),
+            ("a - b", arith::subtract(&x[0], &x[1])),
+            ("a * b", arith::multiply(&x[0], &x[1])),
+            ("-(a * b)", arith::negate(&arith::multiply(&x[0], &x[1]))),
+        ];
+
+        match results.iter().find(|(_, result)| !arith::is_canonical(result)) {
+            Some((what, result)) => Err(format!("{} gave non-canonical {}", what, v2s(result))),
+            None => Ok(()),
+        }
+    }),
+    ("larger is a total order", 2, |x| {
+        let ab = arith::larger(&x[0], &x[1]);
+        let ba = arith::larger(&x[1], &x[0]);
+        let equal = arith::trim(&x[0]) == arith::trim(&x[1]) || is_zero(&x[0]) && is_zero(&x[1]);
+
+        if (ab && ba) != equal || !(ab || ba) {
+            Err(format!("a >= b is {} and b >= a is {}", ab, ba))
+        } else {
+            Ok(())
+        }
+    }),
+];
+
+/// A property that failed, with its operands shrunk as far as they go.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Failure {
+    /// The name of the property, e.g. `"add commutes"`.
+    pub property: &'static str,
+    /// The seed that found the failure.
+    pub seed: u64,
+    /// The shrunk operands.
+    pub operands: Vec<String>,
+    /// What went wrong with the shrunk operands.
+    pub message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "property \"{}\" failed for {} (seed {}): {}",
+            self.property,
+            self.operands.join(", "),
+            self.seed,
+            self.message
+        )
+    }
+}
+
+fn is_zero(a: &[char]) -> bool {
+    a.iter().all(|&c| c == '0' || c == '-')
+}
+
+/// the same value, ignoring leading zeros and the sign of zero
+fn same(what: &str, actual: &[char], expected: &[char]) -> Result<(), String> {
+    let canonical = |a: &[char]| if is_zero(a) { vec!['0'] } else { arith::trim(a) };
+
+    if canonical(actual) == canonical(expected) {
+        Ok(())
+    } else {
+        Err(format!("{} gave {}, expected {}", what, v2s(actual), v2s(expected)))
+    }
+}
+
+fn to_i128(a: &[char]) -> Option<i128> {
+    v2s(a).parse().ok()
+}
+
+fn against_i128(
+    x: &[Operand],
+    reference: fn(i128, i128) -> Option<i128>,
+    f: fn(&[char], &[char]) -> Vec<char>,
+) -> Result<(), String> {
+    match (to_i128(&x[0]), to_i128(&x[1])) {
+        (Some(a), Some(b)) => match reference(a, b) {
+            Some(expected) => same("the result", &f(&x[0], &x[1]), &expected.to_string().chars().collect::<Vec<char>>()),
+            None => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+fn larger_matches_i128(x: &[Operand]) -> Result<(), String> {
+    match (to_i128(&x[0]), to_i128(&x[1])) {
+        (Some(a), Some(b)) if arith::larger(&x[0], &x[1]) != (a >= b) => {
+            Err(format!("a >= b gave {}", a < b))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// check a property, turning a panic into a failure
+fn check(property: fn(&[Operand]) -> Result<(), String>, operands: &[Operand]) -> Result<(), String> {
+    panic::catch_unwind(AssertUnwindSafe(|| property(operands))).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| String::from(*s))
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("unknown panic"));
+
+        Err(format!("panicked: {}", message))
+    })
+}
+
+/// a random operand, biased toward the values arithmetic tends to get wrong
+fn operand(rng: &mut Rng) -> Operand {
+    let mut digits: Vec<char> = match rng.below_u64(8) {
+        // 0 and 1
+        0 => vec![if rng.below_u64(2) == 0 { '0' } else { '1' }],
+        // 10^k
+        1 => {
+            let mut power = vec!['1'];
+            power.extend(vec!['0'; rng.below_u64(45) as usize]);
+            power
+        }
+        // 10^k - 1
+        2 => vec!['9'; rng.below_u64(45) as usize + 1],
+        // long enough for the Karatsuba recursion
+        3 => {
+            let len = 40 + rng.below_u64(120) as usize;
+            random_digits(rng, len)
+        }
+        _ => {
+            let len = 1 + rng.below_u64(30) as usize;
+            random_digits(rng, len)
+        }
+    };
+
+    if rng.below_u64(5) == 0 {
+        let zeros = 1 + rng.below_u64(3) as usize;
+        digits.splice(0..0, vec!['0'; zeros]);
+    }
+
+    if rng.below_u64(2) == 0 {
+        digits.insert(0, '-');
+    }
+
+    digits
+}
+
+fn random_digits(rng: &mut Rng, len: usize) -> Vec<char> {
+    (0..len)
+        .map(|_| core::char::from_digit(rng.below_u64(10) as u32, 10).unwrap())
+        .collect()
+}
+
+/// smaller variations of an operand, simplest first
+fn simpler(a: &[char]) -> Vec<Operand> {
+    let negative = a[0] == '-';
+    let digits = &a[negative as usize..];
+
+    let signed = |digits: &[char]| {
+        let mut v = Vec::with_capacity(digits.len() + 1);
+
+        if negative {
+            v.push('-');
+        }
+
+        v.extend_from_slice(digits);
+        v
+    };
+
+    let mut candidates = vec![vec!['0'], vec!['1']];
+
+    if negative {
+        candidates.push(digits.to_vec());
+    }
+
+    if digits.len() > 1 {
+        candidates.push(signed(&digits[..digits.len() / 2]));
+        candidates.push(signed(&digits[1..]));
+        candidates.push(signed(&digits[..digits.len() - 1]));
+    }
+
+    for (i, &d) in digits.iter().enumerate() {
+        for smaller in ['0', '1'].iter().filter(|&&s| s < d) {
+            let mut v = digits.to_vec();
+            v[i] = *smaller;
+            candidates.push(signed(&v));
+        }
+    }
+
+    // only ever shrinking by length, then lexicographically, guarantees an end
+    candidates.retain(|c| (c.len(), c.as_slice()) < (a.len(), a));
+    candidates
+}
+
+/// greedily replace operands with simpler ones while the property still fails
+fn shrink(property: fn(&[Operand]) -> Result<(), String>, mut operands: Vec<Operand>) -> (Vec<Operand>, String) {
+    let mut message = check(property, &operands).unwrap_err();
+
+    'progress: loop {
+        for i in 0..operands.len() {
+            for candidate in simpler(&operands[i]) {
+                let mut attempt = operands.clone();
+                attempt[i] = candidate;
+
+                if let Err(e) = check(property, &attempt) {
+                    operands = attempt;
+                    message = e;
+                    continue 'progress;
+                }
+            }
+        }
+
+        return (operands, message);
+    }
+}
+
+/// Check every property on `cases` random inputs generated from `seed`.
+///
+/// Returns the first failure found, shrunk to a minimal example.
+pub fn run(seed: u64, cases: usize) -> Result<(), Failure> {
+    let mut rng = Rng::new(seed);
+
+    for _ in 0..cases {
+        for &(name, arity, property) in PROPERTIES {
+            let operands: Vec<Operand> = (0..arity).map(|_| operand(&mut rng)).collect();
+
+            if check(property, &operands).is_err() {
+                let (operands, message) = shrink(property, operands);
+
+                return Err(Failure {
+                    property: name,
+                    seed,
+                    operands: operands.iter().map(|o| v2s(o)).collect(),
+                    message,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(s: &str) -> Operand {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_properties_hold() {
+        for seed in 0..4 {
+            if let Err(failure) = run(seed, 100) {
+                panic!("{}", failure);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shrink() {
+        // fails whenever an operand has a 7 in it
+        let no_sevens: fn(&[Operand]) -> Result<(), String> = |x| {
+            if x.iter().any(|o| o.contains(&'7')) {
+                Err(String::from("found a 7"))
+            } else {
+                Ok(())
+            }
+        };
+
+        let (operands, message) = shrink(no_sevens, vec![digits("-00912737465"), digits("123")]);
+
+        assert_eq!(operands, vec![digits("7"), digits("0")]);
+        assert_eq!(message, "found a 7");
+    }
+
+    #[test]
+    fn test_panics_are_failures() {
+        let result = check(|_| panic!("boom"), &[]);
+
+        assert_eq!(result, Err(String::from("panicked: boom")));
+    }
+
+    #[test]
+    fn test_edge_values() {
+        assert_eq!(same("x", &digits("-0"), &digits("000")), Ok(()));
+        assert!(same("x", &digits("-1"), &digits("1")).is_err());
+        assert!(simpler(&digits("-10")).contains(&digits("10")));
+    }
+}
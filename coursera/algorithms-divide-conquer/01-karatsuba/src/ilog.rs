@@ -0,0 +1,292 @@
+use alloc::string::String;
+
+use crate::arith;
+use crate::BigInt;
+
+/// fractional bits of the fixed point logarithms used for estimates
+const FRACTION_BITS: u32 = 32;
+
+/// the most leading digits that fit in a `u64`
+const LEADING_DIGITS: usize = 19;
+
+/// log2(x) in fixed point, accurate to about 2^-30
+fn log2_fixed(x: u64) -> u128 {
+    let int = x.ilog2();
+
+    // x / 2^int, in [1, 2) with 62 fractional bits
+    let mut y = ((x as u128) << 62) >> int;
+    let mut log = (int as u128) << FRACTION_BITS;
+
+    // squaring doubles the logarithm, shifting its next fractional bit into the integer part
+    for bit in (0..FRACTION_BITS).rev() {
+        y = (y * y) >> 62;
+
+        if y >= 2 << 62 {
+            y >>= 1;
+            log |= 1 << bit;
+        }
+    }
+
+    log
+}
+
+/// `(e, base^e)` with `e = floor(log_base(n))`, for positive `n`
+fn ilog_with_power(n: &BigInt, base: u32) -> (u32, BigInt) {
+    let digits = n.digits();
+    let k = digits.len().min(LEADING_DIGITS);
+    let leading: u64 = digits[..k].iter().collect::<String>().parse().unwrap();
+
+    // estimate from the leading digits and the digit count, which is off by
+    // at most one or two for all but astronomically long numbers
+    let log2_n = log2_fixed(leading) + (digits.len() - k) as u128 * log2_fixed(10);
+    let mut e = (log2_n / log2_fixed(base as u64)) as u32;
+
+    let mut power = BigInt::from(base).pow(e);
+
+    while power > *n {
+        power = BigInt::from_digits(arith::divide_small(power.digits(), base).0);
+        e -= 1;
+    }
+
+    loop {
+        let next = BigInt::from_digits(arith::multiply_small(power.digits(), base, 0));
+
+        if next > *n {
+            return (e, power);
+        }
+
+        power = next;
+        e += 1;
+    }
+}
+
+fn check_log_args(n: &BigInt, base: u32) {
+    assert!(!n.is_negative() && !n.is_zero(), "argument of integer logarithm must be positive");
+    assert!(base >= 2, "base of integer logarithm must be at least 2");
+}
+
+impl BigInt {
+    /// The base `base` logarithm, rounded down.
+    ///
+    /// Estimated from the leading digits and corrected exactly, so it costs
+    /// about one multiplication of numbers the size of `self`, however large
+    /// the result.
+    ///
+    /// ```
+    /// use karatsuba::BigInt;
+    ///
+    /// let n = BigInt::from(3).pow(500);
+    ///
+    /// assert_eq!(n.ilog(3), 500);
+    /// assert_eq!((&n - &BigInt::one()).ilog(3), 499);
+    /// assert_eq!(n.ilog(2), 792);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `self` is not positive, or `base` is less than 2.
+    pub fn ilog(&self, base: u32) -> u32 {
+        check_log_args(self, base);
+
+        if base == 10 {
+            return self.ilog10();
+        }
+
+        ilog_with_power(self, base).0
+    }
+
+    /// The base 2 logarithm, rounded down. See [`BigInt::ilog`].
+    ///
+    /// # Panics
+    ///
+    /// If `self` is not positive.
+    pub fn ilog2(&self) -> u32 {
+        self.ilog(2)
+    }
+
+    /// The base 10 logarithm, rounded down: one less than the digit count.
+    ///
+    /// # Panics
+    ///
+    /// If `self` is not positive.
+    pub fn ilog10(&self) -> u32 {
+        check_log_args(self, 10);
+
+        (self.digit_count() - 1) as u32
+    }
+
+    /// The number of digits of the absolute value written in `base`.
+    ///
+    /// Zero has one digit.
+    ///
+    /// # Panics
+    ///
+    /// If `base` is less than 2.
+    pub fn num_digits(&self, base: u32) -> usize {
+        if self.is_zero() {
+            assert!(base >= 2, "base of integer logarithm must be at least 2");
+            return 1;
+        }
+
+        self.abs().ilog(base) as usize + 1
+    }
+
+    /// Returns `true` if the value is `base^e` for some `e >= 0`.
+    ///
+    /// ```
+    /// use karatsuba::BigInt;
+    ///
+    /// assert!(BigInt::from(2).pow(100).is_power_of(4));
+    /// assert!(!BigInt::from(2).pow(101).is_power_of(4));
+    /// assert!(BigInt::one().is_power_of(7));
+    /// ```
+    pub fn is_power_of(&self, base: u32) -> bool {
+        if self.is_negative() || self.is_zero() {
+            // 0^1 = 0, and no power of a non-negative base is negative
+            return self.is_zero() && base == 0;
+        }
+
+        match base {
+            0 | 1 => *self == BigInt::one(),
+            10 => self.digits()[1..].iter().all(|&d| d == '0') && self.digits()[0] == '1',
+            _ => ilog_with_power(self, base).1 == *self,
+        }
+    }
+
+    /// The sum of the decimal digits of the absolute value.
+    pub fn digit_sum(&self) -> u64 {
+        self.digits()
+            .iter()
+            .filter_map(|d| d.to_digit(10))
+            .map(u64::from)
+            .sum()
+    }
+
+    /// The number of zeros the decimal form ends with, or `None` for zero,
+    /// which has any number of them.
+    pub fn trailing_decimal_zeros(&self) -> Option<usize> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(self.digits().iter().rev().take_while(|&&d| d == '0').count())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{random_bigint, Rng};
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    /// floor(log_base(n)) by repeated division
+    fn slow_ilog(n: &BigInt, base: u32) -> u32 {
+        let mut n = n.clone();
+        let mut e = 0;
+
+        while n >= BigInt::from(base) {
+            n = BigInt::from_digits(arith::divide_small(n.digits(), base).0);
+            e += 1;
+        }
+
+        e
+    }
+
+    fn run_ilog_test(n: &BigInt, base: u32) {
+        let e = n.ilog(base);
+
+        println!("[run_ilog_test] ilog{}(<{} digits>) = {}", base, n.digit_count(), e);
+
+        assert_eq!(e, slow_ilog(n, base));
+    }
+
+    #[test]
+    fn test_log2_fixed() {
+        let one = 1u128 << FRACTION_BITS;
+
+        assert_eq!(log2_fixed(1), 0);
+        assert_eq!(log2_fixed(1 << 40), 40 * one);
+
+        // log2(10) = 3.32192809488736...
+        let log2_10 = log2_fixed(10);
+
+        assert!(log2_10.abs_diff(14267572527) <= 4, "{}", log2_10);
+        assert!(log2_fixed(u64::MAX) < 64 * one);
+    }
+
+    #[test]
+    fn test_ilog() {
+        let mut rng = Rng::new(48);
+
+        for base in [2, 3, 7, 10, 16, 36, 1000, u32::MAX] {
+            for digits in [1, 2, 18, 19, 20, 60, 150] {
+                run_ilog_test(&random_bigint(digits, &mut rng), base);
+            }
+
+            // exact powers and their neighbours are where estimates go wrong
+            for e in [1, 2, 10, 63, 64, 200] {
+                let power = BigInt::from(base).pow(e);
+
+                assert_eq!(power.ilog(base), e);
+                assert_eq!((&power - &BigInt::one()).ilog(base), e - 1);
+                assert_eq!((&power + &BigInt::one()).ilog(base), e);
+            }
+        }
+
+        assert_eq!(BigInt::one().ilog2(), 0);
+        assert_eq!(big("1024").ilog2(), 10);
+        assert_eq!(big("1023").ilog2(), 9);
+        assert_eq!(big("999").ilog10(), 2);
+        assert_eq!(big("1000").ilog10(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "argument of integer logarithm must be positive")]
+    fn test_ilog_of_zero() {
+        BigInt::zero().ilog2();
+    }
+
+    #[test]
+    #[should_panic(expected = "base of integer logarithm must be at least 2")]
+    fn test_ilog_base_one() {
+        big("5").ilog(1);
+    }
+
+    #[test]
+    fn test_num_digits() {
+        assert_eq!(BigInt::zero().num_digits(2), 1);
+        assert_eq!(big("-255").num_digits(16), 2);
+        assert_eq!(big("256").num_digits(16), 3);
+        assert_eq!(big("-12345").num_digits(10), 5);
+        assert_eq!(BigInt::from(2).pow(1000).num_digits(2), 1001);
+    }
+
+    #[test]
+    fn test_is_power_of() {
+        assert!(big("1000000").is_power_of(10));
+        assert!(!big("1000001").is_power_of(10));
+        assert!(!big("2000000").is_power_of(10));
+        assert!(BigInt::from(7).pow(300).is_power_of(7));
+        assert!(BigInt::from(7).pow(300).is_power_of(49));
+        assert!(!BigInt::from(7).pow(301).is_power_of(49));
+        assert!(!(BigInt::from(7).pow(300) + BigInt::one()).is_power_of(7));
+        assert!(!big("-8").is_power_of(2));
+        assert!(!BigInt::zero().is_power_of(2));
+        assert!(BigInt::zero().is_power_of(0));
+        assert!(BigInt::one().is_power_of(0));
+        assert!(!big("2").is_power_of(1));
+    }
+
+    #[test]
+    fn test_digit_metrics() {
+        assert_eq!(big("-9876543210").digit_sum(), 45);
+        assert_eq!(BigInt::zero().digit_sum(), 0);
+        assert_eq!(big("-1200").trailing_decimal_zeros(), Some(2));
+        assert_eq!(big("7").trailing_decimal_zeros(), Some(0));
+        assert_eq!(BigInt::zero().trailing_decimal_zeros(), None);
+        assert_eq!(BigInt::from(10).pow(500).trailing_decimal_zeros(), Some(500));
+    }
+}
@@ -0,0 +1,410 @@
+//! Allocation-free arithmetic on unsigned decimal digit slices.
+//!
+//! Every operand is the big-endian decimal digits `'0'..='9'` of a
+//! non-negative number, the same layout [`BigInt`](crate::BigInt) uses
+//! internally. Leading zeros are allowed and are never trimmed, so results
+//! keep the width of the slice they were written into.
+//!
+//! `BigInt`'s `*` multiplies with [`mul_into`], so a product and every
+//! temporary of its recursion take two allocations, linear in the operand
+//! lengths.
+//!
+//! ```
+//! use karatsuba::inplace::{mul_into, mul_scratch_len};
+//!
+//! let a: Vec<char> = "31415926535897932384".chars().collect();
+//! let b: Vec<char> = "27182818284590452353".chars().collect();
+//!
+//! let mut product = vec!['0'; a.len() + b.len()];
+//! let mut scratch = vec!['0'; mul_scratch_len(a.len(), b.len())];
+//!
+//! mul_into(&mut product, &a, &b, &mut scratch);
+//!
+//! assert_eq!(product.iter().collect::<String>(), "0853973422267356706510386982199967699552");
+//! ```
+
+use core::char;
+use core::cmp::{max, min};
+
+use crate::tuning;
+
+fn digit(c: char) -> u32 {
+    c.to_digit(10).unwrap()
+}
+
+fn to_char(d: u32) -> char {
+    char::from_digit(d, 10).unwrap()
+}
+
+/// a += b, aligning the last digits, and returns true if the sum carried out of `a`
+pub fn add_assign(a: &mut [char], b: &[char]) -> bool {
+    assert!(a.len() >= b.len(), "Trying to add a longer number in place");
+
+    let offset = a.len() - b.len();
+
+    let mut carry: u32 = 0;
+
+    for i in (0..a.len()).rev() {
+        let b_val = if i >= offset {
+            digit(b[i - offset])
+        } else if carry == 0 {
+            break;
+        } else {
+            0
+        };
+
+        let sum = digit(a[i]) + b_val + carry;
+
+        a[i] = to_char(sum % 10);
+        carry = sum / 10;
+    }
+
+    carry != 0
+}
+
+/// a -= b, aligning the last digits, and returns true if b was larger
+///
+/// On a borrow `a` is left holding the ten's complement, 10^len + a - b.
+pub fn sub_assign(a: &mut [char], b: &[char]) -> bool {
+    assert!(a.len() >= b.len(), "Trying to subtract a longer number in place");
+
+    let offset = a.len() - b.len();
+
+    let mut borrow: u32 = 0;
+
+    for i in (0..a.len()).rev() {
+        let b_val = if i >= offset {
+            digit(b[i - offset])
+        } else if borrow == 0 {
+            break;
+        } else {
+            0
+        };
+
+        let a_val = digit(a[i]);
+
+        a[i] = if a_val >= b_val + borrow {
+            let d = a_val - b_val - borrow;
+            borrow = 0;
+            to_char(d)
+        } else {
+            let d = a_val + 10 - b_val - borrow;
+            borrow = 1;
+            to_char(d)
+        };
+    }
+
+    borrow != 0
+}
+
+/// the number of scratch digits [`mul_into`] needs for operands of these lengths
+///
+/// This is linear in the operand lengths, about four times the shorter one.
+/// It depends on the [Karatsuba threshold](crate::tuning), so size the
+/// scratch space again after changing the thresholds.
+pub fn mul_scratch_len(a_len: usize, b_len: usize) -> usize {
+    scratch_len(a_len, b_len, tuning::thresholds().karatsuba)
+}
+
+pub(crate) fn scratch_len(a_len: usize, b_len: usize, threshold: usize) -> usize {
+    let long = max(a_len, b_len);
+    let short = min(a_len, b_len);
+
+    if short < threshold {
+        0
+    } else if long == short {
+        let mid = long - long / 2;
+
+        // the two half sums, their product, then whatever that product needs
+        4 * (mid + 1) + scratch_len(mid + 1, mid + 1, threshold)
+    } else {
+        let rem = long % short;
+
+        let chunk = scratch_len(short, short, threshold);
+        let tail = if rem > 0 { scratch_len(rem, short, threshold) } else { 0 };
+
+        2 * short + max(chunk, tail)
+    }
+}
+
+/// dst = a * b, where dst is exactly `a.len() + b.len()` digits long
+///
+/// `scratch` must hold at least [`mul_scratch_len`] digits; its contents on
+/// entry are ignored and on return are unspecified. Nothing is allocated.
+pub fn mul_into(dst: &mut [char], a: &[char], b: &[char], scratch: &mut [char]) {
+    multiply_with(dst, a, b, scratch, tuning::thresholds().karatsuba);
+}
+
+/// [`mul_into`] with an explicit Karatsuba threshold of at least 4
+pub(crate) fn multiply_with(dst: &mut [char], a: &[char], b: &[char], scratch: &mut [char], threshold: usize) {
+    check_lengths(dst, a, b, scratch, threshold);
+
+    multiply(dst, a, b, scratch, threshold, &mut || false);
+}
+
+/// [`mul_into`], asking `stop` at every Karatsuba step whether to give up
+///
+/// Returns false if it gave up, leaving `dst` unspecified. Once `stop` has
+/// returned true, the rest of the recursion only unwinds.
+pub(crate) fn mul_into_until(
+    dst: &mut [char],
+    a: &[char],
+    b: &[char],
+    scratch: &mut [char],
+    mut stop: impl FnMut() -> bool,
+) -> bool {
+    let threshold = tuning::thresholds().karatsuba;
+
+    check_lengths(dst, a, b, scratch, threshold);
+
+    let mut stopped = false;
+
+    multiply(dst, a, b, scratch, threshold, &mut || {
+        stopped = stopped || stop();
+        stopped
+    });
+
+    !stopped
+}
+
+fn check_lengths(dst: &[char], a: &[char], b: &[char], scratch: &[char], threshold: usize) {
+    assert_eq!(dst.len(), a.len() + b.len(), "Product slice has the wrong length");
+    assert!(
+        scratch.len() >= scratch_len(a.len(), b.len(), threshold),
+        "Scratch space is too small"
+    );
+}
+
+fn multiply<F>(dst: &mut [char], a: &[char], b: &[char], scratch: &mut [char], threshold: usize, stop: &mut F)
+where
+    F: FnMut() -> bool,
+{
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+
+    if short.len() < threshold {
+        schoolbook(dst, long, short);
+    } else if long.len() == short.len() {
+        karatsuba(dst, long, short, scratch, threshold, stop);
+    } else {
+        unbalanced(dst, long, short, scratch, threshold, stop);
+    }
+}
+
+/// grade-school multiplication straight into `dst`
+pub(crate) fn schoolbook(dst: &mut [char], a: &[char], b: &[char]) {
+    for d in dst.iter_mut() {
+        *d = '0';
+    }
+
+    for j in (0..b.len()).rev() {
+        let b_val = digit(b[j]);
+
+        if b_val == 0 {
+            continue;
+        }
+
+        let mut carry: u32 = 0;
+
+        for i in (0..a.len()).rev() {
+            let k = i + j + 1;
+            let t = digit(dst[k]) + digit(a[i]) * b_val + carry;
+
+            dst[k] = to_char(t % 10);
+            carry = t / 10;
+        }
+
+        // rows are accumulated right to left, so dst[j] is still zero here
+        dst[j] = to_char(carry);
+    }
+}
+
+/// one Karatsuba step on equal length operands, with z0 and z2 written in place
+fn karatsuba<F>(dst: &mut [char], a: &[char], b: &[char], scratch: &mut [char], threshold: usize, stop: &mut F)
+where
+    F: FnMut() -> bool,
+{
+    if stop() {
+        return;
+    }
+
+    let shift = a.len() / 2;
+    let mid = a.len() - shift;
+
+    let (ah, al) = a.split_at(mid);
+    let (bh, bl) = b.split_at(mid);
+
+    {
+        let (z2, z0) = dst.split_at_mut(2 * mid);
+
+        multiply(z0, al, bl, scratch, threshold, stop);
+        multiply(z2, ah, bh, scratch, threshold, stop);
+    }
+
+    let (sums, rest) = scratch.split_at_mut(2 * (mid + 1));
+    let (a_sum, b_sum) = sums.split_at_mut(mid + 1);
+
+    a_sum[0] = '0';
+    a_sum[1..].copy_from_slice(ah);
+    add_assign(a_sum, al);
+
+    b_sum[0] = '0';
+    b_sum[1..].copy_from_slice(bh);
+    add_assign(b_sum, bl);
+
+    let (z1, rest) = rest.split_at_mut(2 * (mid + 1));
+
+    multiply(z1, a_sum, b_sum, rest, threshold, stop);
+
+    // z3 = z1 - z2 - z0
+    sub_assign(z1, &dst[2 * mid..]);
+    sub_assign(z1, &dst[..2 * mid]);
+
+    let end = dst.len() - shift;
+    add_assign(&mut dst[..end], z1);
+}
+
+/// multiply `long` by `short` one `short`-sized chunk at a time
+fn unbalanced<F>(dst: &mut [char], long: &[char], short: &[char], scratch: &mut [char], threshold: usize, stop: &mut F)
+where
+    F: FnMut() -> bool,
+{
+    for d in dst.iter_mut() {
+        *d = '0';
+    }
+
+    let n = short.len();
+    let (partial, rest) = scratch.split_at_mut(2 * n);
+
+    let mut end = long.len();
+
+    while end > 0 {
+        let start = end.saturating_sub(n);
+        let chunk = &long[start..end];
+
+        let product = &mut partial[..chunk.len() + n];
+
+        multiply(product, chunk, short, rest, threshold, stop);
+        add_assign(&mut dst[..end + n], product);
+
+        end = start;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arith;
+
+    fn digits(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    fn run_mul_into_test(a: &str, b: &str) {
+        let a = digits(a);
+        let b = digits(b);
+
+        let mut product = vec!['x'; a.len() + b.len()];
+        let mut scratch = vec!['x'; mul_scratch_len(a.len(), b.len())];
+
+        mul_into(&mut product, &a, &b, &mut scratch);
+
+        let expected = arith::pad(&long_multiply(&a, &b), a.len() + b.len());
+
+        println!("[run_mul_into_test] {} x {} = {}", arith::v2s(&a), arith::v2s(&b), arith::v2s(&product));
+
+        assert_eq!(product, expected);
+
+        for &threshold in [4, 5, 9].iter() {
+            let mut scratch = vec!['x'; scratch_len(a.len(), b.len(), threshold)];
+
+            multiply_with(&mut product, &a, &b, &mut scratch, threshold);
+
+            assert_eq!(product, expected);
+        }
+    }
+
+    /// reference product built from single-digit multiplications
+    fn long_multiply(a: &[char], b: &[char]) -> Vec<char> {
+        let mut total = vec!['0'];
+
+        for (i, d) in b.iter().enumerate() {
+            let mut row = arith::multiply_small(a, d.to_digit(10).unwrap(), 0);
+            row.extend(vec!['0'; b.len() - i - 1]);
+            total = arith::add(&total, &row);
+        }
+
+        total
+    }
+
+    #[test]
+    fn test_add_assign() {
+        let mut a = digits("0999");
+
+        assert!(!add_assign(&mut a, &digits("1")));
+        assert_eq!(a, digits("1000"));
+
+        let mut a = digits("999");
+
+        assert!(add_assign(&mut a, &digits("001")));
+        assert_eq!(a, digits("000"));
+    }
+
+    #[test]
+    fn test_sub_assign() {
+        let mut a = digits("1000");
+
+        assert!(!sub_assign(&mut a, &digits("1")));
+        assert_eq!(a, digits("0999"));
+
+        let mut a = digits("005");
+
+        assert!(sub_assign(&mut a, &digits("7")));
+        assert_eq!(a, digits("998"));
+    }
+
+    #[test]
+    fn test_mul_into() {
+        run_mul_into_test("0", "0");
+        run_mul_into_test("1234", "5678");
+        run_mul_into_test("99999999", "99999999");
+        run_mul_into_test("000012345678901234567890", "98765432109876543210");
+        run_mul_into_test("3141592653589793238462643383279502884197169399375105820974944592",
+                          "2718281828459045235360287471352662497757247093699959574966967627");
+        run_mul_into_test(&"9".repeat(201), &"9".repeat(37));
+        run_mul_into_test(&"123456789".repeat(13), &"987654321".repeat(29));
+    }
+
+    #[test]
+    fn test_mul_into_until() {
+        let a = digits(&"7".repeat(200));
+
+        let mut product = vec!['x'; 400];
+        let mut scratch = vec!['x'; mul_scratch_len(200, 200)];
+
+        let mut steps = 0;
+
+        assert!(mul_into_until(&mut product, &a, &a, &mut scratch, || {
+            steps += 1;
+            false
+        }));
+        assert_eq!(product, arith::pad(&long_multiply(&a, &a), 400));
+        assert!(steps > 1);
+
+        // once it has said stop it is not asked again
+        let mut asked = 0;
+
+        assert!(!mul_into_until(&mut product, &a, &a, &mut scratch, || {
+            asked += 1;
+            asked == 3
+        }));
+        assert_eq!(asked, 3);
+    }
+
+    #[test]
+    fn test_scratch_is_linear() {
+        for n in [10, 100, 1000, 10000, 100000].iter() {
+            assert!(mul_scratch_len(*n, *n) <= 5 * n);
+            assert!(mul_scratch_len(3 * n, *n) <= 7 * n);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use alloc::vec::Vec;
+use core::cmp::min;
+
+/// a positional number system: numbers are vectors of positions, weighted
+/// by powers of a base `B`
+///
+/// Decimal digits have `B = 10` and polynomial coefficients `B = x`, so one
+/// Karatsuba step splits and recombines both the same way. `Tally` lets an
+/// implementation count the work each operation does.
+pub(crate) trait Positional {
+    type Position: Clone;
+    type Tally;
+
+    /// the high part and the low `shift` positions, so n = high·B^shift + low
+    fn split(
+        n: &[Self::Position],
+        shift: usize,
+        tally: &mut Self::Tally,
+    ) -> (Vec<Self::Position>, Vec<Self::Position>);
+
+    fn add(
+        a: &[Self::Position],
+        b: &[Self::Position],
+        tally: &mut Self::Tally,
+    ) -> Vec<Self::Position>;
+
+    fn sub(
+        a: &[Self::Position],
+        b: &[Self::Position],
+        tally: &mut Self::Tally,
+    ) -> Vec<Self::Position>;
+
+    /// n·B^by
+    fn shift(n: Vec<Self::Position>, by: usize, tally: &mut Self::Tally) -> Vec<Self::Position>;
+}
+
+/// the operands of the three smaller products of a karatsuba step
+pub(crate) struct Halves<P> {
+    /// the positions in the low parts
+    pub(crate) shift: usize,
+    pub(crate) ah: Vec<P>,
+    pub(crate) al: Vec<P>,
+    pub(crate) bh: Vec<P>,
+    pub(crate) bl: Vec<P>,
+    pub(crate) a_sum: Vec<P>,
+    pub(crate) b_sum: Vec<P>,
+}
+
+/// split both operands at half the length of the shorter one
+///
+/// The products al·bl, a_sum·b_sum and ah·bh are then z0, z1 and z2.
+pub(crate) fn halve<S: Positional>(
+    a: &[S::Position],
+    b: &[S::Position],
+    tally: &mut S::Tally,
+) -> Halves<S::Position> {
+    let shift = min(a.len(), b.len()) / 2;
+
+    let (ah, al) = S::split(a, shift, tally);
+    let (bh, bl) = S::split(b, shift, tally);
+
+    let a_sum = S::add(&al, &ah, tally);
+    let b_sum = S::add(&bl, &bh, tally);
+
+    Halves {
+        shift,
+        ah,
+        al,
+        bh,
+        bl,
+        a_sum,
+        b_sum,
+    }
+}
+
+/// z3 = z1 - z2 - z0, the cross terms al·bh + ah·bl
+pub(crate) fn cross_terms<S: Positional>(
+    z0: &[S::Position],
+    z1: &[S::Position],
+    z2: &[S::Position],
+    tally: &mut S::Tally,
+) -> Vec<S::Position> {
+    let z1_minus_z2 = S::sub(z1, z2, tally);
+
+    S::sub(&z1_minus_z2, z0, tally)
+}
+
+/// z2·B^(2·shift) + z3·B^shift + z0
+pub(crate) fn combine<S: Positional>(
+    z0: &[S::Position],
+    z2: Vec<S::Position>,
+    z3: Vec<S::Position>,
+    shift: usize,
+    tally: &mut S::Tally,
+) -> Vec<S::Position> {
+    let z2 = S::shift(z2, 2 * shift, tally);
+    let z3 = S::shift(z3, shift, tally);
+
+    let partial = S::add(&z2, &z3, tally);
+
+    S::add(&partial, z0, tally)
+}
@@ -0,0 +1,64 @@
+//! Arbitrary precision integer arithmetic built around Karatsuba
+//! multiplication, from the Coursera divide-and-conquer algorithms course.
+//!
+//! The public API is [`BigInt`] together with its parsing, formatting,
+//! comparison, conversion and arithmetic operator impls, the [`product`]
+//! and [`sum`] functions, and the [`format`](mod@format), [`inplace`],
+//! [`limits`], [`polynomial`], [`modular`], [`random`], [`trace`] and
+//! [`tuning`] modules. Everything else is an implementation detail and may
+//! change between releases.
+//!
+//! C bindings are in the `karatsuba-ffi` crate in the `ffi` directory, with a
+//! header in `ffi/include/karatsuba.h`.
+//!
+//! ```
+//! use karatsuba::BigInt;
+//!
+//! let a: BigInt = "2718281828459045235360287471352662497757247093699959574966967627".parse().unwrap();
+//! let b: BigInt = "3141592653589793238462643383279502884197169399375105820974944592".parse().unwrap();
+//!
+//! let product = &a * &b;
+//!
+//! assert_eq!(product.digit_count(), 127);
+//! assert!(product > a);
+//! ```
+//!
+//! # Features
+//!
+//! * `std` (default): implement `std::error::Error` for the error types,
+//!   stream digits with `BigInt::write_decimal` and `BigInt::read_decimal`,
+//!   load, save and measure the [`tuning`] thresholds, give operations a
+//!   [`limits`] deadline, and check the arithmetic with the `fuzz` module.
+//!   Without it the crate is `#![no_std]` and only needs `alloc`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![warn(missing_docs)]
+
+extern crate alloc;
+
+mod arith;
+mod bigint;
+mod convert;
+pub mod format;
+#[cfg(feature = "std")]
+pub mod fuzz;
+mod ilog;
+pub mod inplace;
+mod kernel;
+pub mod limits;
+pub mod modular;
+pub mod polynomial;
+pub mod random;
+mod stats;
+#[cfg(feature = "std")]
+mod stream;
+pub mod trace;
+mod tree;
+pub mod tuning;
+#[cfg(test)]
+mod vectors;
+
+pub use bigint::{BigInt, ParseBigIntError};
+pub use convert::{TryFromBigIntError, TryFromFloatError};
+pub use stats::MulStats;
+pub use tree::{product, sum};
@@ -0,0 +1,205 @@
+//! Bounds on the work done for untrusted input.
+//!
+//! Multiplying two numbers of `n` digits takes memory proportional to `n` and
+//! time proportional to n^log2(3), so input from users should be bounded
+//! before it is multiplied. [`BigInt::mul_limited`] checks the operand and
+//! result sizes up front, and with the `std` feature gives up once a deadline
+//! passes, returning a [`LimitExceeded`] error instead of the product.
+//!
+//! ```
+//! use karatsuba::limits::{LimitExceeded, Limits};
+//! use karatsuba::BigInt;
+//!
+//! let mut limits = Limits::default();
+//! limits.max_result_digits = Some(100);
+//!
+//! let a: BigInt = "9".repeat(60).parse().unwrap();
+//!
+//! assert_eq!(a.mul_limited(&BigInt::from(9), &limits), Ok(&a * &BigInt::from(9)));
+//! assert_eq!(
+//!     a.mul_limited(&a, &limits),
+//!     Err(LimitExceeded::ResultDigits { digits: 120, max: 100 })
+//! );
+//! ```
+
+use core::fmt;
+
+use crate::arith::{self, Probe};
+use crate::BigInt;
+
+/// The largest operations [`BigInt::mul_limited`] will do.
+///
+/// Every limit is off by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Limits {
+    /// The most digits either operand may have.
+    pub max_input_digits: Option<usize>,
+    /// The most digits the result may have. Checked before any work is done,
+    /// against the largest result the operand lengths allow.
+    pub max_result_digits: Option<usize>,
+    /// Give up once this instant passes. Checked at every Karatsuba step.
+    #[cfg(feature = "std")]
+    pub deadline: Option<std::time::Instant>,
+}
+
+/// The error returned when an operation would exceed its [`Limits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LimitExceeded {
+    /// An operand has more digits than allowed.
+    InputDigits {
+        /// The digits in the operand.
+        digits: usize,
+        /// The limit.
+        max: usize,
+    },
+    /// The result could have more digits than allowed.
+    ResultDigits {
+        /// The most digits the result could have.
+        digits: usize,
+        /// The limit.
+        max: usize,
+    },
+    /// The deadline passed before the operation finished.
+    Deadline,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitExceeded::InputDigits { digits, max } => {
+                write!(f, "operand has {} digits, more than the limit of {}", digits, max)
+            }
+            LimitExceeded::ResultDigits { digits, max } => {
+                write!(f, "result could have {} digits, more than the limit of {}", digits, max)
+            }
+            LimitExceeded::Deadline => write!(f, "deadline passed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LimitExceeded {}
+
+impl Limits {
+    /// Check that `n` is small enough to be an operand.
+    pub fn check_input(&self, n: &BigInt) -> Result<(), LimitExceeded> {
+        match self.max_input_digits {
+            Some(max) if n.digit_count() > max => Err(LimitExceeded::InputDigits {
+                digits: n.digit_count(),
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// check a result that could have up to `digits` digits
+    fn check_result(&self, digits: usize) -> Result<(), LimitExceeded> {
+        match self.max_result_digits {
+            Some(max) if digits > max => Err(LimitExceeded::ResultDigits { digits, max }),
+            _ => Ok(()),
+        }
+    }
+
+    /// a probe that gives up at the deadline
+    fn probe(&self) -> Probe {
+        #[cfg(feature = "std")]
+        return Probe::until(self.deadline);
+
+        #[cfg(not(feature = "std"))]
+        Probe::default()
+    }
+}
+
+impl BigInt {
+    /// Multiply, unless the operands, the result or the time taken would
+    /// exceed `limits`.
+    ///
+    /// The sizes are checked before multiplying, so an oversized operation
+    /// fails without allocating anything. Otherwise this multiplies in place
+    /// like `*`, in two allocations of about `a + b` and `4 * min(a, b)`
+    /// digits for operands of `a` and `b` digits. A deadline is checked at
+    /// every Karatsuba step, so it is overrun by at most one base case
+    /// multiplication and the additions that unwind the recursion.
+    pub fn mul_limited(&self, other: &BigInt, limits: &Limits) -> Result<BigInt, LimitExceeded> {
+        limits.check_input(self)?;
+        limits.check_input(other)?;
+        limits.check_result(self.digit_count() + other.digit_count())?;
+
+        let mut probe = limits.probe();
+
+        if probe.out_of_time() {
+            return Err(LimitExceeded::Deadline);
+        }
+
+        arith::multiply_until(self.digits(), other.digits(), || probe.out_of_time())
+            .map(BigInt::from_digits)
+            .ok_or(LimitExceeded::Deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_sizes() {
+        let mut limits = Limits::default();
+        let a = big("-123456");
+
+        assert_eq!(a.mul_limited(&a, &limits), Ok(big("15241383936")));
+
+        limits.max_input_digits = Some(5);
+
+        assert_eq!(a.mul_limited(&big("1"), &limits), Err(LimitExceeded::InputDigits { digits: 6, max: 5 }));
+        assert_eq!(big("1").mul_limited(&a, &limits), Err(LimitExceeded::InputDigits { digits: 6, max: 5 }));
+        assert_eq!(big("-12345").mul_limited(&big("12345"), &limits), Ok(big("-152399025")));
+
+        limits.max_input_digits = None;
+        limits.max_result_digits = Some(11);
+
+        // 123456 * 123456 has 11 digits, but 999999 * 999999 would have 12
+        assert_eq!(a.mul_limited(&a, &limits), Err(LimitExceeded::ResultDigits { digits: 12, max: 11 }));
+        assert_eq!(a.mul_limited(&big("99999"), &limits), Ok(big("-12345476544")));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_deadline() {
+        use std::time::{Duration, Instant};
+
+        let mut limits = Limits::default();
+        let a = big(&"7".repeat(20000));
+
+        limits.deadline = Some(Instant::now());
+
+        assert_eq!(big("2").mul_limited(&big("3"), &limits), Err(LimitExceeded::Deadline));
+
+        limits.deadline = Some(Instant::now() + Duration::from_millis(5));
+
+        let start = Instant::now();
+        let result = a.mul_limited(&a, &limits);
+
+        println!("[test_deadline] gave up after {:?}", start.elapsed());
+
+        assert_eq!(result, Err(LimitExceeded::Deadline));
+
+        limits.deadline = Some(Instant::now() + Duration::from_secs(3600));
+
+        assert_eq!(big("-99").mul_limited(&big("99"), &limits), Ok(big("-9801")));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            LimitExceeded::InputDigits { digits: 6, max: 5 }.to_string(),
+            "operand has 6 digits, more than the limit of 5"
+        );
+        assert_eq!(LimitExceeded::Deadline.to_string(), "deadline passed");
+    }
+}
@@ -0,0 +1,655 @@
+//! Integers modulo a fixed modulus.
+//!
+//! [`ModP`] has a compile-time `u64` modulus and can be a polynomial
+//! coefficient. [`ModInt`] has a [`BigInt`] modulus chosen at runtime and
+//! shared by reference, and its operators reduce as they go:
+//!
+//! ```
+//! use karatsuba::modular::{crt, ModInt, Modulus};
+//! use karatsuba::BigInt;
+//!
+//! // 2^127 - 1 is prime
+//! let p = Modulus::new("170141183460469231731687303715884105727".parse().unwrap());
+//!
+//! let a = ModInt::new(&BigInt::from(-3), &p);
+//! let b = a.inverse().unwrap();
+//!
+//! assert_eq!((&a * &b).value(), &BigInt::one());
+//! assert_eq!((&a * &a).sqrt().unwrap().value(), &BigInt::from(3));
+//!
+//! // x = 2 mod 3, x = 3 mod 5 and x = 2 mod 7
+//! let congruences = [(2, 3), (3, 5), (2, 7)].map(|(a, n)| (BigInt::from(a), BigInt::from(n)));
+//!
+//! assert_eq!(crt(&congruences), Some(BigInt::from(23)));
+//! ```
+
+use core::fmt;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use crate::arith;
+use crate::polynomial::Ring;
+use crate::BigInt;
+
+/// An integer modulo the compile-time modulus `M`, always kept in `0..M`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ModP<const M: u64> {
+    value: u64,
+}
+
+impl<const M: u64> ModP<M> {
+    /// `value` reduced modulo `M`.
+    ///
+    /// # Panics
+    ///
+    /// If `M` is 0.
+    pub fn new(value: u64) -> Self {
+        ModP { value: value % M }
+    }
+
+    /// A signed `value` reduced modulo `M`.
+    ///
+    /// # Panics
+    ///
+    /// If `M` is 0.
+    pub fn from_i64(value: i64) -> Self {
+        ModP {
+            // in i128, since moduli past i64::MAX would wrap to negative as i64
+            value: (value as i128).rem_euclid(M as i128) as u64,
+        }
+    }
+
+    /// The canonical representative in `0..M`.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Raise to the power `exp` by repeated squaring.
+    pub fn pow(&self, mut exp: u64) -> Self {
+        let mut base = *self;
+        let mut result = ModP::new(1);
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * base;
+            }
+
+            base = base * base;
+            exp >>= 1;
+        }
+
+        result
+    }
+}
+
+impl<const M: u64> fmt::Display for ModP<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<const M: u64> Add for ModP<M> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        ModP {
+            value: ((self.value as u128 + other.value as u128) % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> Sub for ModP<M> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + (-other)
+    }
+}
+
+impl<const M: u64> Mul for ModP<M> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        ModP {
+            value: ((self.value as u128 * other.value as u128) % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> Neg for ModP<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        ModP {
+            value: (M - self.value) % M,
+        }
+    }
+}
+
+impl<const M: u64> Ring for ModP<M> {
+    fn zero() -> Self {
+        ModP::new(0)
+    }
+
+    fn one() -> Self {
+        ModP::new(1)
+    }
+
+    /// extended Euclid, so composite moduli work for any unit
+    fn inverse(&self) -> Option<Self> {
+        let (mut old_r, mut r) = (self.value as i128, M as i128);
+        let (mut old_s, mut s) = (1i128, 0i128);
+
+        while r != 0 {
+            let q = old_r / r;
+
+            let next_r = old_r - q * r;
+            old_r = r;
+            r = next_r;
+
+            let next_s = old_s - q * s;
+            old_s = s;
+            s = next_s;
+        }
+
+        if old_r != 1 {
+            return None;
+        }
+
+        Some(ModP {
+            value: old_s.rem_euclid(M as i128) as u64,
+        })
+    }
+}
+
+/// `a mod n`, in `0..n`
+fn rem_euclid(a: &BigInt, n: &BigInt) -> BigInt {
+    let r = a % n;
+
+    if r.is_negative() {
+        r + n
+    } else {
+        r
+    }
+}
+
+/// extended Euclid: `(g, x)` with `g = gcd(a, b)` and `a * x = g (mod b)`
+fn gcd_ext(a: &BigInt, b: &BigInt) -> (BigInt, BigInt) {
+    let (mut old_r, mut r) = (a.clone(), b.clone());
+    let (mut old_s, mut s) = (BigInt::one(), BigInt::zero());
+
+    while !r.is_zero() {
+        let (q, next_r) = old_r.div_rem(&r);
+        old_r = core::mem::replace(&mut r, next_r);
+
+        let next_s = &old_s - &(&q * &s);
+        old_s = core::mem::replace(&mut s, next_s);
+    }
+
+    (old_r, old_s)
+}
+
+/// A modulus shared by the [`ModInt`]s that refer to it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Modulus {
+    m: BigInt,
+}
+
+impl Modulus {
+    /// A modulus of `m`.
+    ///
+    /// # Panics
+    ///
+    /// If `m` is less than 1.
+    pub fn new(m: BigInt) -> Modulus {
+        assert!(m >= BigInt::one(), "the modulus must be positive");
+
+        Modulus { m }
+    }
+
+    /// The modulus itself.
+    pub fn value(&self) -> &BigInt {
+        &self.m
+    }
+}
+
+/// An integer modulo a runtime [`Modulus`], always kept in `0..m`.
+///
+/// Combining `ModInt`s with different moduli panics.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ModInt<'m> {
+    value: BigInt,
+    modulus: &'m Modulus,
+}
+
+impl<'m> ModInt<'m> {
+    /// `value` reduced modulo `modulus`.
+    pub fn new(value: &BigInt, modulus: &'m Modulus) -> ModInt<'m> {
+        ModInt {
+            value: rem_euclid(value, &modulus.m),
+            modulus,
+        }
+    }
+
+    /// The canonical representative in `0..m`.
+    pub fn value(&self) -> &BigInt {
+        &self.value
+    }
+
+    /// The modulus.
+    pub fn modulus(&self) -> &'m Modulus {
+        self.modulus
+    }
+
+    /// the same modulus with another value
+    fn with(&self, value: &BigInt) -> ModInt<'m> {
+        ModInt::new(value, self.modulus)
+    }
+
+    fn check(&self, other: &ModInt) {
+        assert!(
+            core::ptr::eq(self.modulus, other.modulus) || self.modulus == other.modulus,
+            "ModInt moduli differ"
+        );
+    }
+
+    /// The multiplicative inverse, if `gcd(value, m) = 1`.
+    pub fn inverse(&self) -> Option<ModInt<'m>> {
+        let (g, x) = gcd_ext(&self.value, &self.modulus.m);
+
+        if g == BigInt::one() {
+            Some(self.with(&x))
+        } else {
+            None
+        }
+    }
+
+    /// Raise to the power `exp` by repeated squaring.
+    ///
+    /// # Panics
+    ///
+    /// If `exp` is negative.
+    pub fn pow(&self, exp: &BigInt) -> ModInt<'m> {
+        assert!(!exp.is_negative(), "negative exponent");
+
+        let mut base = self.clone();
+        let mut result = self.with(&BigInt::one());
+        let mut exp = exp.clone();
+
+        while !exp.is_zero() {
+            let (half, bit) = arith::divide_small(exp.digits(), 2);
+
+            if bit == 1 {
+                result = &result * &base;
+            }
+
+            exp = BigInt::from_digits(half);
+
+            if !exp.is_zero() {
+                base = &base * &base;
+            }
+        }
+
+        result
+    }
+
+    /// A square root, if there is one, when the modulus is prime.
+    ///
+    /// Uses Tonelli–Shanks. This returns the smaller of the two roots; the
+    /// other is its negation. Composite moduli give `None` or a
+    /// meaningless result, but always promptly.
+    pub fn sqrt(&self) -> Option<ModInt<'m>> {
+        let one = BigInt::one();
+        let p = &self.modulus.m;
+
+        if self.value.is_zero() || self.value == one || *p == BigInt::from(2) {
+            return Some(self.clone());
+        }
+
+        let p_minus_1 = p - &one;
+        let half = &p_minus_1 / &BigInt::from(2);
+
+        // Euler's criterion
+        if self.pow(&half).value != one {
+            return None;
+        }
+
+        // p - 1 = q * 2^s with q odd
+        let mut q = p_minus_1.clone();
+        let mut s = 0u32;
+
+        loop {
+            let (next, bit) = arith::divide_small(q.digits(), 2);
+
+            if bit == 1 {
+                break;
+            }
+
+            q = BigInt::from_digits(next);
+            s += 1;
+        }
+
+        let smaller = |r: ModInt<'m>| {
+            let other = -&r;
+
+            if other.value < r.value {
+                other
+            } else {
+                r
+            }
+        };
+
+        // p - 1 is odd, so p is even and not prime
+        if s == 0 {
+            return None;
+        }
+
+        // for p = 3 (mod 4) the root is a power of a, with no need for a non-residue
+        if s == 1 {
+            return Some(smaller(self.pow(&(&(p + &one) / &BigInt::from(4)))));
+        }
+
+        // any quadratic non-residue. For prime p one of the first 2 ln²(p)
+        // candidates is (assuming GRH), while composite moduli may have none,
+        // so give up past that bound. ln(10) < 7/3.
+        let ln_p = p.digit_count() * 7 / 3 + 1;
+        let mut z = self.with(&BigInt::from(2));
+        let mut candidates = 2 * ln_p * ln_p;
+
+        while z.pow(&half).value != p_minus_1 {
+            z = &z + &self.with(&one);
+            candidates -= 1;
+
+            if z.value.is_zero() || candidates == 0 {
+                return None;
+            }
+        }
+
+        let mut m = s;
+        let mut c = z.pow(&q);
+        let mut t = self.pow(&q);
+        let mut r = self.pow(&(&(&q + &one) / &BigInt::from(2)));
+
+        while t.value != one {
+            // the least i with t^(2^i) = 1
+            let mut i = 0;
+            let mut t_squared = t.clone();
+
+            while t_squared.value != one {
+                t_squared = &t_squared * &t_squared;
+                i += 1;
+
+                if i == m {
+                    return None;
+                }
+            }
+
+            let mut b = c.clone();
+
+            for _ in 0..m - i - 1 {
+                b = &b * &b;
+            }
+
+            m = i;
+            c = &b * &b;
+            t = &t * &c;
+            r = &r * &b;
+        }
+
+        Some(smaller(r))
+    }
+}
+
+impl fmt::Display for ModInt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.value, f)
+    }
+}
+
+/// implement a reducing binary operator for every combination of owned and borrowed operands
+macro_rules! impl_modop {
+    ($trait:ident, $method:ident) => {
+        impl<'m> $trait<&ModInt<'m>> for &ModInt<'m> {
+            type Output = ModInt<'m>;
+
+            fn $method(self, other: &ModInt<'m>) -> ModInt<'m> {
+                self.check(other);
+                self.with(&(&self.value).$method(&other.value))
+            }
+        }
+
+        impl<'m> $trait<ModInt<'m>> for &ModInt<'m> {
+            type Output = ModInt<'m>;
+
+            fn $method(self, other: ModInt<'m>) -> ModInt<'m> {
+                self.$method(&other)
+            }
+        }
+
+        impl<'m> $trait<&ModInt<'m>> for ModInt<'m> {
+            type Output = ModInt<'m>;
+
+            fn $method(self, other: &ModInt<'m>) -> ModInt<'m> {
+                (&self).$method(other)
+            }
+        }
+
+        impl<'m> $trait<ModInt<'m>> for ModInt<'m> {
+            type Output = ModInt<'m>;
+
+            fn $method(self, other: ModInt<'m>) -> ModInt<'m> {
+                (&self).$method(&other)
+            }
+        }
+    };
+}
+
+impl_modop!(Add, add);
+impl_modop!(Sub, sub);
+impl_modop!(Mul, mul);
+
+impl<'m> Neg for &ModInt<'m> {
+    type Output = ModInt<'m>;
+
+    fn neg(self) -> ModInt<'m> {
+        self.with(&-&self.value)
+    }
+}
+
+impl<'m> Neg for ModInt<'m> {
+    type Output = ModInt<'m>;
+
+    fn neg(self) -> ModInt<'m> {
+        -&self
+    }
+}
+
+/// Solve a system of congruences `x = residue (mod modulus)` with the
+/// Chinese remainder theorem.
+///
+/// Returns the least non-negative solution, which is unique modulo the
+/// least common multiple of the moduli, or `None` if the congruences
+/// contradict each other. The moduli need not be coprime.
+///
+/// # Panics
+///
+/// If a modulus is less than 1.
+pub fn crt(congruences: &[(BigInt, BigInt)]) -> Option<BigInt> {
+    let mut x = BigInt::zero();
+    let mut lcm = BigInt::one();
+
+    for (residue, n) in congruences {
+        assert!(*n >= BigInt::one(), "the modulus must be positive");
+
+        // find k with x + lcm * k = residue (mod n), knowing lcm * inv = g (mod n)
+        let (g, inv) = gcd_ext(&lcm, n);
+        let (q, r) = (residue - &x).div_rem(&g);
+
+        if !r.is_zero() {
+            return None;
+        }
+
+        let n_over_g = n / &g;
+        let k = rem_euclid(&(&q * &inv), &n_over_g);
+
+        x = &x + &(&lcm * &k);
+        lcm = &lcm * &n_over_g;
+    }
+
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type F7 = ModP<7>;
+    type Z12 = ModP<12>;
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(F7::new(5) + F7::new(4), F7::new(2));
+        assert_eq!(F7::new(2) - F7::new(5), F7::new(4));
+        assert_eq!(F7::new(3) * F7::new(5), F7::new(1));
+        assert_eq!(-F7::new(0), F7::new(0));
+        assert_eq!(F7::from_i64(-1), F7::new(6));
+        assert_eq!(F7::new(3).pow(6), F7::new(1));
+    }
+
+    #[test]
+    fn test_inverse() {
+        for v in 1..7 {
+            let x = F7::new(v);
+
+            assert_eq!(x * x.inverse().unwrap(), F7::one());
+        }
+
+        assert_eq!(F7::new(0).inverse(), None);
+        assert_eq!(Z12::new(5).inverse(), Some(Z12::new(5)));
+        assert_eq!(Z12::new(4).inverse(), None);
+    }
+
+    #[test]
+    fn test_large_modulus() {
+        type P = ModP<18446744073709551557>;
+
+        let a = P::new(18446744073709551556);
+
+        assert_eq!(a * a, P::one());
+        assert_eq!(a + a, P::new(18446744073709551555));
+        assert_eq!(P::from_i64(-1), a);
+        assert_eq!(P::from_i64(i64::MIN).value(), 18446744073709551557 - (1 << 63));
+        assert_eq!(P::from_i64(i64::MAX).value(), i64::MAX as u64);
+    }
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    /// 2^127 - 1
+    const MERSENNE: &str = "170141183460469231731687303715884105727";
+
+    #[test]
+    fn test_mod_int() {
+        let p = Modulus::new(big(MERSENNE));
+        let x = |s: &str| ModInt::new(&big(s), &p);
+
+        assert_eq!(x("-1").value(), &big("170141183460469231731687303715884105726"));
+        assert_eq!(x(MERSENNE).value(), &BigInt::zero());
+        assert_eq!(&x("-1") + &x("5"), x("4"));
+        assert_eq!(x("3") - x("5"), x("-2"));
+        assert_eq!(-x("0"), x("0"));
+        assert_eq!((x("2").pow(&big("127"))).value(), &BigInt::one());
+        assert_eq!(x("12345678901234567890").pow(&(big(MERSENNE) - BigInt::one())), x("1"));
+        assert_eq!(x("7").pow(&BigInt::zero()), x("1"));
+        assert_eq!(format!("{:>4}", x("-170141183460469231731687303715884105700")), "  27");
+
+        let a = x("98765432109876543210987654321");
+
+        assert_eq!(&a * &a.inverse().unwrap(), x("1"));
+        assert_eq!(x("0").inverse(), None);
+
+        let m12 = Modulus::new(big("12"));
+
+        assert_eq!(ModInt::new(&big("5"), &m12).inverse(), Some(ModInt::new(&big("5"), &m12)));
+        assert_eq!(ModInt::new(&big("4"), &m12).inverse(), None);
+
+        let one = Modulus::new(BigInt::one());
+
+        assert_eq!(ModInt::new(&big("-7"), &one).value(), &BigInt::zero());
+    }
+
+    #[test]
+    #[should_panic(expected = "ModInt moduli differ")]
+    fn test_moduli_differ() {
+        let (p, q) = (Modulus::new(big("7")), Modulus::new(big("11")));
+
+        let _ = ModInt::new(&big("1"), &p) + ModInt::new(&big("1"), &q);
+    }
+
+    fn run_sqrt_test(p: &str, values: &[&str]) {
+        let p = Modulus::new(big(p));
+
+        for v in values {
+            let a = ModInt::new(&big(v), &p);
+            let square = &a * &a;
+            let root = square.sqrt().unwrap();
+
+            println!("[run_sqrt_test] sqrt({}) = {} mod {}", square, root, p.value());
+
+            assert_eq!(&root * &root, square);
+            assert!(root == a || root == -&a);
+        }
+    }
+
+    #[test]
+    fn test_sqrt() {
+        // p = 3 (mod 4), p = 5 (mod 8), and 998244353 = 119 * 2^23 + 1
+        run_sqrt_test(MERSENNE, &["3", "123456789012345678901234567890"]);
+        run_sqrt_test("13", &["0", "1", "2", "3", "4", "5", "6"]);
+        run_sqrt_test("998244353", &["2", "3", "31337", "998244352", "123456789"]);
+        run_sqrt_test("2", &["0", "1"]);
+
+        // composite moduli must not hang, even when no candidate is a non-residue
+        let m = Modulus::new(big("4"));
+
+        assert_eq!(ModInt::new(&big("1"), &m).sqrt(), Some(ModInt::new(&big("1"), &m)));
+        assert!(ModInt::new(&big("3"), &m).sqrt().is_none());
+
+        // 5 * 1000000021 = 1 (mod 8)
+        let m = Modulus::new(big("5000000105"));
+
+        assert_eq!(ModInt::new(&big("1"), &m).sqrt(), Some(ModInt::new(&big("1"), &m)));
+
+        // -1 mod 1000000021 and 1 mod 5 passes Euler's criterion, but since
+        // (m - 1) / 2 is a multiple of 4, no z has z^((m - 1) / 2) = -1 mod 5,
+        // and the non-residue search has to give up
+        assert_eq!(ModInt::new(&big("2000000041"), &m).sqrt(), None);
+
+        let p = Modulus::new(big("998244353"));
+
+        // 3 generates the multiplicative group, so it is not a square
+        assert_eq!(ModInt::new(&big("3"), &p).sqrt(), None);
+        assert_eq!(ModInt::new(&big("9"), &p).sqrt(), Some(ModInt::new(&big("3"), &p)));
+    }
+
+    #[test]
+    fn test_crt() {
+        let system = |pairs: &[(&str, &str)]| -> Vec<(BigInt, BigInt)> {
+            pairs.iter().map(|&(a, n)| (big(a), big(n))).collect()
+        };
+
+        assert_eq!(crt(&[]), Some(BigInt::zero()));
+        assert_eq!(crt(&system(&[("2", "3"), ("3", "5"), ("2", "7")])), Some(big("23")));
+        assert_eq!(crt(&system(&[("-1", "4"), ("3", "6")])), Some(big("3")));
+        assert_eq!(crt(&system(&[("1", "4"), ("2", "6")])), None);
+
+        // a 90 digit number from its residues modulo a few large primes
+        let x = big(&"1234567890".repeat(9));
+        let primes = [MERSENNE, "998244353", "18446744073709551557", "1000000007", "2305843009213693951"];
+        let moduli: Vec<BigInt> = primes.iter().map(|p| big(p)).collect();
+
+        let residues: Vec<(BigInt, BigInt)> = moduli.iter().map(|n| (&x % n, n.clone())).collect();
+
+        assert_eq!(crt(&residues), Some(x));
+    }
+}
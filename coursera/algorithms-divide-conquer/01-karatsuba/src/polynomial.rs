@@ -0,0 +1,581 @@
+//! Dense univariate polynomials over a coefficient [`Ring`].
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use crate::kernel::{self, Halves};
+use crate::tuning;
+
+/// The coefficient arithmetic a polynomial needs.
+pub trait Ring:
+    Clone + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+    /// The additive identity.
+    fn zero() -> Self;
+
+    /// The multiplicative identity.
+    fn one() -> Self;
+
+    /// Returns true if this is the additive identity.
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    /// The multiplicative inverse, if this element is a unit.
+    fn inverse(&self) -> Option<Self>;
+}
+
+macro_rules! impl_ring_for_int {
+    ($($t:ty),*) => {
+        $(
+            impl Ring for $t {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn one() -> Self {
+                    1
+                }
+
+                fn inverse(&self) -> Option<Self> {
+                    if *self == 1 || *self == -1 {
+                        Some(*self)
+                    } else {
+                        None
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_ring_for_int!(i32, i64, i128);
+
+/// A dense polynomial, stored lowest degree first with no trailing zero coefficients.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial<T> {
+    coeffs: Vec<T>,
+}
+
+impl<T: Ring> Polynomial<T> {
+    /// Build a polynomial from its coefficients, lowest degree first.
+    ///
+    /// Trailing zero coefficients are dropped.
+    pub fn new(coeffs: Vec<T>) -> Self {
+        let mut p = Polynomial { coeffs };
+        p.normalize();
+        p
+    }
+
+    /// The zero polynomial, which has no coefficients.
+    pub fn zero() -> Self {
+        Polynomial { coeffs: Vec::new() }
+    }
+
+    /// The constant polynomial `c`.
+    pub fn constant(c: T) -> Self {
+        Polynomial::new(vec![c])
+    }
+
+    /// The monic linear factor `x - root`.
+    pub fn linear(root: T) -> Self {
+        Polynomial::new(vec![-root, T::one()])
+    }
+
+    /// The coefficients, lowest degree first.
+    pub fn coeffs(&self) -> &[T] {
+        &self.coeffs
+    }
+
+    /// The coefficient of `x^i`, which is zero past the degree.
+    pub fn coeff(&self, i: usize) -> T {
+        self.coeffs.get(i).cloned().unwrap_or_else(T::zero)
+    }
+
+    /// The degree, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        if self.coeffs.is_empty() {
+            None
+        } else {
+            Some(self.coeffs.len() - 1)
+        }
+    }
+
+    /// Returns true for the zero polynomial.
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    /// The highest degree coefficient, or `None` for the zero polynomial.
+    pub fn leading(&self) -> Option<&T> {
+        self.coeffs.last()
+    }
+
+    /// Multiply every coefficient by `c`.
+    pub fn scale(&self, c: &T) -> Self {
+        Polynomial::new(self.coeffs.iter().map(|a| a.clone() * c.clone()).collect())
+    }
+
+    /// Multiply by `x^n`.
+    pub fn shift(&self, n: usize) -> Self {
+        if self.is_zero() {
+            return self.clone();
+        }
+
+        let mut coeffs = vec![T::zero(); n];
+        coeffs.extend_from_slice(&self.coeffs);
+
+        Polynomial { coeffs }
+    }
+
+    /// The grade-school product, in O(n²) coefficient products.
+    pub fn mul_schoolbook(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+
+        Polynomial::new(schoolbook(&self.coeffs, &other.coeffs))
+    }
+
+    /// The product by Karatsuba multiplication, falling back to the
+    /// grade-school product below the tuned threshold.
+    pub fn mul_karatsuba(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+
+        Polynomial::new(karatsuba(&self.coeffs, &other.coeffs))
+    }
+
+    /// Divide by `divisor`, returning `(quotient, remainder)`.
+    ///
+    /// Returns `None` if the divisor is zero or its leading coefficient is not
+    /// a unit, since the quotient is then not defined over `T`.
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        let lead_inv = divisor.leading()?.inverse()?;
+        let d = divisor.coeffs.len();
+
+        if self.coeffs.len() < d {
+            return Some((Polynomial::zero(), self.clone()));
+        }
+
+        let mut remainder = self.coeffs.clone();
+        let mut quotient = vec![T::zero(); remainder.len() - d + 1];
+
+        for i in (0..quotient.len()).rev() {
+            let q = remainder[i + d - 1].clone() * lead_inv.clone();
+
+            if !q.is_zero() {
+                for (j, c) in divisor.coeffs.iter().enumerate() {
+                    remainder[i + j] = remainder[i + j].clone() - q.clone() * c.clone();
+                }
+            }
+
+            quotient[i] = q;
+        }
+
+        remainder.truncate(d - 1);
+
+        Some((Polynomial::new(quotient), Polynomial::new(remainder)))
+    }
+
+    /// Evaluate at `x` using Horner's rule.
+    pub fn evaluate(&self, x: &T) -> T {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(T::zero(), |acc, c| acc * x.clone() + c.clone())
+    }
+
+    /// Evaluate at every point in `xs`, in order.
+    ///
+    /// Reduces the polynomial down a tree of products of `x - xs[i]`, which
+    /// takes fewer coefficient operations than Horner's rule at every point
+    /// once there are many points.
+    pub fn evaluate_many(&self, xs: &[T]) -> Vec<T> {
+        let mut values = Vec::with_capacity(xs.len());
+
+        if let Some(tree) = SubproductTree::build(xs) {
+            tree.evaluate(self, &mut values);
+        }
+
+        values
+    }
+
+    /// The unique polynomial of degree less than `xs.len()` through the
+    /// points `(xs[i], ys[i])`.
+    ///
+    /// Uses Lagrange's formula. Returns `None` if some difference
+    /// `xs[i] - xs[j]` is not invertible, for example with a repeated point,
+    /// or with integer coefficients.
+    ///
+    /// # Panics
+    ///
+    /// If `xs` and `ys` have different lengths.
+    pub fn interpolate(xs: &[T], ys: &[T]) -> Option<Self> {
+        assert_eq!(xs.len(), ys.len(), "Interpolating with mismatched point counts");
+
+        let tree = match SubproductTree::build(xs) {
+            Some(tree) => tree,
+            None => return Some(Polynomial::zero()),
+        };
+
+        let mut result = Polynomial::zero();
+
+        for (x, y) in xs.iter().zip(ys) {
+            let (basis, _) = tree.poly.div_rem(&Polynomial::linear(x.clone()))?;
+            let weight = y.clone() * basis.evaluate(x).inverse()?;
+
+            result = result + basis.scale(&weight);
+        }
+
+        Some(result)
+    }
+
+    fn normalize(&mut self) {
+        while self.coeffs.last().is_some_and(|c| c.is_zero()) {
+            self.coeffs.pop();
+        }
+    }
+}
+
+/// products of (x - x_i) over ever larger ranges of points
+struct SubproductTree<T> {
+    poly: Polynomial<T>,
+    children: Option<Box<(SubproductTree<T>, SubproductTree<T>)>>,
+}
+
+impl<T: Ring> SubproductTree<T> {
+    fn build(xs: &[T]) -> Option<Self> {
+        match xs.len() {
+            0 => None,
+            1 => Some(SubproductTree {
+                poly: Polynomial::linear(xs[0].clone()),
+                children: None,
+            }),
+            n => {
+                let left = SubproductTree::build(&xs[..n / 2])?;
+                let right = SubproductTree::build(&xs[n / 2..])?;
+
+                Some(SubproductTree {
+                    poly: &left.poly * &right.poly,
+                    children: Some(Box::new((left, right))),
+                })
+            }
+        }
+    }
+
+    fn evaluate(&self, f: &Polynomial<T>, values: &mut Vec<T>) {
+        // every node is monic, so the division always succeeds
+        let (_, r) = f.div_rem(&self.poly).unwrap();
+
+        match &self.children {
+            None => values.push(r.coeff(0)),
+            Some(children) => {
+                children.0.evaluate(&r, values);
+                children.1.evaluate(&r, values);
+            }
+        }
+    }
+}
+
+/// grade-school product of two coefficient slices, lowest degree first
+fn schoolbook<T: Ring>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut product = vec![T::zero(); a.len() + b.len() - 1];
+
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] = product[i + j].clone() + x.clone() * y.clone();
+        }
+    }
+
+    product
+}
+
+/// coefficient-wise sum, as long as the longer operand
+fn add_coeffs<T: Ring>(a: &[T], b: &[T]) -> Vec<T> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+
+    let mut sum = long.to_vec();
+
+    for (s, c) in sum.iter_mut().zip(short) {
+        *s = s.clone() + c.clone();
+    }
+
+    sum
+}
+
+/// coefficient-wise difference, as long as the longer operand
+fn sub_coeffs<T: Ring>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut diff = a.to_vec();
+
+    diff.resize(core::cmp::max(a.len(), b.len()), T::zero());
+
+    for (d, c) in diff.iter_mut().zip(b) {
+        *d = d.clone() - c.clone();
+    }
+
+    diff
+}
+
+/// coefficients lowest degree first, for the shared karatsuba step, where
+/// shifting by n multiplies by x^n
+struct Coefficients<T>(PhantomData<T>);
+
+impl<T: Ring> kernel::Positional for Coefficients<T> {
+    type Position = T;
+    type Tally = ();
+
+    fn split(n: &[T], shift: usize, _: &mut ()) -> (Vec<T>, Vec<T>) {
+        let (low, high) = n.split_at(shift);
+
+        (high.to_vec(), low.to_vec())
+    }
+
+    fn add(a: &[T], b: &[T], _: &mut ()) -> Vec<T> {
+        add_coeffs(a, b)
+    }
+
+    fn sub(a: &[T], b: &[T], _: &mut ()) -> Vec<T> {
+        sub_coeffs(a, b)
+    }
+
+    fn shift(n: Vec<T>, by: usize, _: &mut ()) -> Vec<T> {
+        let mut shifted = vec![T::zero(); by];
+
+        shifted.extend(n);
+        shifted
+    }
+}
+
+/// Karatsuba product of two coefficient slices, lowest degree first
+///
+/// Takes the same step as the digit version, below the same tuned threshold
+/// falling back to schoolbook multiplication.
+fn karatsuba<T: Ring>(a: &[T], b: &[T]) -> Vec<T> {
+    let threshold = tuning::thresholds().karatsuba;
+
+    if a.len() < threshold || b.len() < threshold {
+        return schoolbook(a, b);
+    }
+
+    let Halves { shift, ah, al, bh, bl, a_sum, b_sum } = kernel::halve::<Coefficients<T>>(a, b, &mut ());
+
+    let z0 = karatsuba(&al, &bl);
+    let z1 = karatsuba(&a_sum, &b_sum);
+    let z2 = karatsuba(&ah, &bh);
+
+    let z3 = kernel::cross_terms::<Coefficients<T>>(&z0, &z1, &z2, &mut ());
+
+    kernel::combine::<Coefficients<T>>(&z0, z2, z3, shift, &mut ())
+}
+
+impl<T: Ring> Add for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn add(self, other: Self) -> Polynomial<T> {
+        Polynomial::new(add_coeffs(&self.coeffs, &other.coeffs))
+    }
+}
+
+impl<T: Ring> Add for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn add(self, other: Self) -> Polynomial<T> {
+        &self + &other
+    }
+}
+
+impl<T: Ring> Neg for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn neg(self) -> Polynomial<T> {
+        Polynomial {
+            coeffs: self.coeffs.iter().map(|c| -c.clone()).collect(),
+        }
+    }
+}
+
+impl<T: Ring> Neg for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn neg(self) -> Polynomial<T> {
+        -&self
+    }
+}
+
+impl<T: Ring> Sub for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn sub(self, other: Self) -> Polynomial<T> {
+        self + &(-other)
+    }
+}
+
+impl<T: Ring> Sub for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn sub(self, other: Self) -> Polynomial<T> {
+        &self - &other
+    }
+}
+
+impl<T: Ring> Mul for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn mul(self, other: Self) -> Polynomial<T> {
+        self.mul_karatsuba(other)
+    }
+}
+
+impl<T: Ring> Mul for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn mul(self, other: Self) -> Polynomial<T> {
+        &self * &other
+    }
+}
+
+impl<T: Ring + fmt::Display> fmt::Display for Polynomial<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut first = true;
+
+        for (i, c) in self.coeffs.iter().enumerate().rev() {
+            if c.is_zero() {
+                continue;
+            }
+
+            if !first {
+                write!(f, " + ")?;
+            }
+
+            match i {
+                0 => write!(f, "{}", c)?,
+                1 => write!(f, "{}x", c)?,
+                _ => write!(f, "{}x^{}", c, i)?,
+            }
+
+            first = false;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modular::ModP;
+
+    type F = ModP<998244353>;
+
+    fn poly(coeffs: &[i64]) -> Polynomial<i64> {
+        Polynomial::new(coeffs.to_vec())
+    }
+
+    fn fpoly(coeffs: &[i64]) -> Polynomial<F> {
+        Polynomial::new(coeffs.iter().map(|&c| F::from_i64(c)).collect())
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(poly(&[1, 2, 0, 0]).coeffs(), &[1, 2]);
+        assert_eq!(poly(&[0, 0]).degree(), None);
+        assert_eq!(poly(&[0, 0, 3]).degree(), Some(2));
+    }
+
+    #[test]
+    fn test_add_sub() {
+        assert_eq!(poly(&[1, 2, 3]) + poly(&[4, 5]), poly(&[5, 7, 3]));
+        assert_eq!(poly(&[1, 2, 3]) - poly(&[1, 2, 3]), Polynomial::zero());
+        assert_eq!(poly(&[1, 2]) - poly(&[0, 0, 7]), poly(&[1, 2, -7]));
+    }
+
+    fn run_multiply_test(a: &[i64], b: &[i64]) {
+        let a = poly(a);
+        let b = poly(b);
+
+        let expected = a.mul_schoolbook(&b);
+
+        println!("[run_multiply_test] ({}) x ({}) = {}", a, b, expected);
+
+        assert_eq!(a.mul_karatsuba(&b), expected);
+    }
+
+    #[test]
+    fn test_multiply() {
+        run_multiply_test(&[1, 1], &[-1, 1]);
+        run_multiply_test(&[], &[1, 2, 3]);
+        run_multiply_test(&(1..40).collect::<Vec<i64>>(), &(-20..20).collect::<Vec<i64>>());
+        run_multiply_test(&(0..100).map(|i| i * i - 7).collect::<Vec<i64>>(), &[3; 17]);
+        run_multiply_test(&[5; 64], &[-2; 63]);
+
+        assert_eq!(poly(&[1, 1]) * poly(&[1, 1]), poly(&[1, 2, 1]));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let a = poly(&[-4, 0, -2, 1]);
+        let b = poly(&[-3, 1]);
+
+        let (q, r) = a.div_rem(&b).unwrap();
+
+        assert_eq!(q, poly(&[3, 1, 1]));
+        assert_eq!(r, poly(&[5]));
+        assert_eq!(&(&q * &b) + &r, a);
+
+        // 2x has no inverse leading coefficient over the integers
+        assert_eq!(a.div_rem(&poly(&[0, 2])), None);
+        assert_eq!(a.div_rem(&Polynomial::zero()), None);
+
+        let (q, r) = fpoly(&[1, 2, 3, 4, 5]).div_rem(&fpoly(&[7, 0, 2])).unwrap();
+
+        assert_eq!(&(&q * &fpoly(&[7, 0, 2])) + &r, fpoly(&[1, 2, 3, 4, 5]));
+        assert_eq!(r.degree(), Some(1));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let p = poly(&[1, -3, 0, 2]);
+
+        assert_eq!(p.evaluate(&0), 1);
+        assert_eq!(p.evaluate(&2), 11);
+        assert_eq!(p.evaluate(&-1), 2);
+        assert_eq!(Polynomial::<i64>::zero().evaluate(&5), 0);
+
+        let xs: Vec<i64> = (-10..10).collect();
+        let expected: Vec<i64> = xs.iter().map(|x| p.evaluate(x)).collect();
+
+        assert_eq!(p.evaluate_many(&xs), expected);
+        assert_eq!(p.evaluate_many(&[]), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let p = fpoly(&[3, 0, -1, 4, 1]);
+
+        let xs: Vec<F> = (1..=5).map(F::new).collect();
+        let ys = p.evaluate_many(&xs);
+
+        assert_eq!(Polynomial::interpolate(&xs, &ys), Some(p));
+
+        // repeated points have no unique interpolant
+        let xs = vec![F::new(1), F::new(1)];
+
+        assert_eq!(Polynomial::interpolate(&xs, &[F::new(2), F::new(3)]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Interpolating with mismatched point counts")]
+    fn test_interpolate_mismatched() {
+        Polynomial::interpolate(&[F::new(1), F::new(2)], &[F::new(3)]);
+    }
+}
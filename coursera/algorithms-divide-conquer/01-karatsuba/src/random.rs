@@ -0,0 +1,255 @@
+//! Reproducible random [`BigInt`]s for benchmarks and testing.
+//!
+//! [`Rng`] is a small xoshiro256** generator: fast, statistically sound and
+//! fully determined by its seed, but not suitable for cryptography.
+//!
+//! ```
+//! use karatsuba::random::{random_below, random_bigint, random_range, Rng};
+//! use karatsuba::BigInt;
+//!
+//! let mut rng = Rng::new(42);
+//!
+//! let a = random_bigint(100, &mut rng);
+//! let b = random_below(&a, &mut rng);
+//! let c = random_range(&BigInt::from(-5), &BigInt::from(5), &mut rng);
+//!
+//! assert_eq!(a.digit_count(), 100);
+//! assert!(b < a);
+//! assert!(c >= BigInt::from(-5) && c < BigInt::from(5));
+//!
+//! // the same seed gives the same numbers
+//! assert_eq!(random_bigint(100, &mut Rng::new(42)), a);
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::BigInt;
+
+/// The largest power of ten that fits in a `u64`, and its exponent.
+const CHUNK: u64 = 10_000_000_000_000_000_000;
+const CHUNK_DIGITS: usize = 19;
+
+/// A seedable xoshiro256** pseudorandom number generator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    /// A generator whose whole output is determined by `seed`.
+    ///
+    /// The state is filled from the seed with SplitMix64, as the xoshiro
+    /// authors recommend, so nearby seeds give unrelated streams.
+    pub fn new(seed: u64) -> Rng {
+        let mut seed = seed;
+
+        let mut splitmix = || {
+            seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+
+        Rng {
+            state: [splitmix(), splitmix(), splitmix(), splitmix()],
+        }
+    }
+
+    /// The next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+
+    /// A uniformly random integer in `0..n`.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    pub fn below_u64(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "empty range");
+
+        // reject the top partial copy of 0..n so every value is equally likely
+        let zone = n * (u64::MAX / n);
+
+        loop {
+            let x = self.next_u64();
+
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+
+    /// `count` uniformly random decimal digits.
+    fn digits(&mut self, count: usize) -> String {
+        let mut digits = String::with_capacity(count + CHUNK_DIGITS);
+
+        while digits.len() < count {
+            digits.push_str(&format!("{:019}", self.below_u64(CHUNK)));
+        }
+
+        digits.truncate(count);
+        digits
+    }
+}
+
+/// A uniformly random non-negative integer with exactly `digits` digits.
+///
+/// The leading digit is never zero, so the result lies in
+/// `10^(digits - 1)..10^digits`. Zero digits gives zero.
+pub fn random_bigint(digits: usize, rng: &mut Rng) -> BigInt {
+    if digits == 0 {
+        return BigInt::zero();
+    }
+
+    let leading = (b'1' + rng.below_u64(9) as u8) as char;
+
+    let mut text = String::with_capacity(digits);
+    text.push(leading);
+    text.push_str(&rng.digits(digits - 1));
+
+    BigInt::from_digits(text.chars().collect())
+}
+
+/// A uniformly random integer in `0..n`.
+///
+/// Candidates are drawn with the same number of digits as `n` and a leading
+/// digit no larger than its own, and rejected until one is below `n`; more
+/// than half are accepted.
+///
+/// # Panics
+///
+/// If `n` is not positive.
+pub fn random_below(n: &BigInt, rng: &mut Rng) -> BigInt {
+    assert!(!n.is_negative() && !n.is_zero(), "random_below needs a positive bound");
+
+    let digits = n.digits();
+    let leading = digits[0].to_digit(10).unwrap() as u64;
+
+    loop {
+        let mut text = String::with_capacity(digits.len());
+        text.push(core::char::from_digit(rng.below_u64(leading + 1) as u32, 10).unwrap());
+        text.push_str(&rng.digits(digits.len() - 1));
+
+        let candidate = BigInt::from_digits(text.chars().collect());
+
+        if candidate < *n {
+            return candidate;
+        }
+    }
+}
+
+/// A uniformly random integer in `lo..hi`.
+///
+/// # Panics
+///
+/// If `hi <= lo`.
+pub fn random_range(lo: &BigInt, hi: &BigInt, rng: &mut Rng) -> BigInt {
+    assert!(lo < hi, "random_range needs lo < hi");
+
+    lo + random_below(&(hi - lo), rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_reference_stream() {
+        // xoshiro256** seeded from SplitMix64(0), per the reference C code
+        let mut rng = Rng::new(0);
+
+        assert_eq!(rng.next_u64(), 0x99ec_5f36_cb75_f2b4);
+        assert_eq!(rng.next_u64(), 0xbf6e_1f78_4956_452a);
+        assert_eq!(rng.next_u64(), 0x1a5f_849d_4933_e6e0);
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+
+        assert_eq!(random_bigint(500, &mut a), random_bigint(500, &mut b));
+        assert_ne!(random_bigint(500, &mut a), random_bigint(500, &mut Rng::new(8)));
+    }
+
+    #[test]
+    fn test_random_bigint() {
+        let mut rng = Rng::new(1);
+
+        assert_eq!(random_bigint(0, &mut rng), BigInt::zero());
+
+        for digits in 1..60 {
+            let n = random_bigint(digits, &mut rng);
+
+            assert_eq!(n.digit_count(), digits);
+            assert!(!n.is_negative());
+        }
+    }
+
+    #[test]
+    fn test_random_below_is_uniform() {
+        let mut rng = Rng::new(2);
+        let mut counts = [0u32; 12];
+
+        for _ in 0..12000 {
+            let n = random_below(&BigInt::from(12), &mut rng);
+
+            counts[u8::try_from(n).unwrap() as usize] += 1;
+        }
+
+        println!("[test_random_below_is_uniform] {:?}", counts);
+
+        // each count is 1000 on average with a standard deviation of about 30
+        assert!(counts.iter().all(|&count| (850..1150).contains(&count)));
+    }
+
+    #[test]
+    fn test_random_range() {
+        let mut rng = Rng::new(3);
+
+        let lo = big("-100000000000000000000000000000");
+        let hi = big("-99999999999999999999999999990");
+
+        let mut seen = alloc::vec::Vec::new();
+
+        for _ in 0..200 {
+            let n = random_range(&lo, &hi, &mut rng);
+
+            assert!(n >= lo && n < hi);
+
+            if !seen.contains(&n) {
+                seen.push(n);
+            }
+        }
+
+        assert_eq!(seen.len(), 10);
+        assert_eq!(random_range(&big("5"), &big("6"), &mut rng), big("5"));
+    }
+
+    #[test]
+    #[should_panic(expected = "random_below needs a positive bound")]
+    fn test_random_below_zero() {
+        random_below(&BigInt::zero(), &mut Rng::new(0));
+    }
+}
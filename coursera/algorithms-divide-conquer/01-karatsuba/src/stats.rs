@@ -0,0 +1,113 @@
+use crate::arith::{self, Probe};
+use crate::BigInt;
+
+/// Counts of the work done by one multiplication.
+///
+/// Returned by [`BigInt::mul_with_stats`]. Operands small enough to multiply
+/// natively or with the schoolbook method end the recursion, and count as
+/// one digit product for every pair of their digits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MulStats {
+    /// Calls to the Karatsuba step, including the outermost one.
+    pub karatsuba_calls: u64,
+    /// The deepest nesting of Karatsuba steps; `0` if none was needed.
+    pub max_depth: usize,
+    /// Single digit products done in the base cases.
+    pub base_products: u64,
+    /// Additions and subtractions done by the Karatsuba steps.
+    pub add_subs: u64,
+    /// Total length of every intermediate number allocated.
+    pub digits_allocated: u64,
+}
+
+impl MulStats {
+    /// The digit products schoolbook multiplication needs for `n` digit operands, n².
+    pub fn schoolbook_products(n: usize) -> f64 {
+        (n as f64) * (n as f64)
+    }
+
+    /// The digit products Karatsuba needs for `n` digit operands when it
+    /// recurses all the way down to single digits, n^log2(3).
+    #[cfg(feature = "std")]
+    pub fn karatsuba_products(n: usize) -> f64 {
+        (n as f64).powf(3f64.log2())
+    }
+}
+
+impl BigInt {
+    /// Multiply, also returning counts of the work the algorithm did.
+    ///
+    /// The counts describe the recursive Karatsuba that allocates each
+    /// intermediate number, which this runs instead of the in-place
+    /// [`mul_into`](crate::inplace::mul_into) behind `*`. The product is the
+    /// same either way.
+    ///
+    /// ```
+    /// use karatsuba::BigInt;
+    ///
+    /// let a: BigInt = "9".repeat(500).parse().unwrap();
+    /// let (product, stats) = a.mul_with_stats(&a);
+    ///
+    /// assert_eq!(product, &a * &a);
+    /// assert!(stats.karatsuba_calls > 0);
+    /// ```
+    pub fn mul_with_stats(&self, other: &BigInt) -> (BigInt, MulStats) {
+        let mut probe = Probe::default();
+
+        let product = arith::multiply_probed(self.digits(), other.digits(), &mut probe);
+
+        (BigInt::from_digits(product), probe.stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_has_no_recursion() {
+        let a = BigInt::from(1234);
+        let (product, stats) = a.mul_with_stats(&BigInt::from(-5678));
+
+        assert_eq!(product, BigInt::from(-7006652));
+        assert_eq!(stats.karatsuba_calls, 0);
+        assert_eq!(stats.max_depth, 0);
+        assert_eq!(stats.base_products, 16);
+        assert_eq!(stats.add_subs, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_large_recurses() {
+        let len = core::cmp::max(1000, arith::karatsuba_digits(2));
+
+        let a: BigInt = "3".repeat(len).parse().unwrap();
+        let b: BigInt = "7".repeat(len).parse().unwrap();
+
+        let (product, stats) = a.mul_with_stats(&b);
+
+        println!("[test_large_recurses] {:?}", stats);
+
+        assert_eq!(product, a.mul_schoolbook(&b));
+        assert!(stats.max_depth >= 2);
+        assert!(stats.karatsuba_calls >= 3);
+        assert_eq!(stats.add_subs, 6 * stats.karatsuba_calls);
+
+        // fewer digit products than schoolbook, but no fewer than the leaves allow
+        let leaf = crate::tuning::thresholds().karatsuba as f64;
+
+        assert!((stats.base_products as f64) < MulStats::schoolbook_products(len));
+        assert!((stats.base_products as f64) > MulStats::karatsuba_products(len) / leaf);
+        assert!(stats.digits_allocated > 2 * len as u64);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_theoretical_counts() {
+        assert_eq!(MulStats::schoolbook_products(64), 4096.0);
+        assert!((MulStats::karatsuba_products(64) - 729.0).abs() < 1e-6);
+        assert!((MulStats::karatsuba_products(1000) - 56870.56).abs() < 0.01);
+        assert_eq!(MulStats::karatsuba_products(1), 1.0);
+    }
+}
@@ -0,0 +1,207 @@
+use std::io::{self, Read, Write};
+use std::vec::Vec;
+
+use crate::{BigInt, ParseBigIntError};
+
+/// bytes converted per write or read call
+const CHUNK: usize = 8192;
+
+/// how far `read_decimal` has got through the number
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// skipping whitespace before the number
+    Leading,
+    /// after the sign, before the first digit
+    Signed,
+    Digits,
+    /// skipping whitespace after the number
+    Trailing,
+}
+
+fn invalid(e: ParseBigIntError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+impl BigInt {
+    /// Write the decimal digits to `w`, a chunk at a time.
+    ///
+    /// Unlike `to_string`, this never holds a second copy of the number, so
+    /// it suits results with millions of digits. The digits are stored in
+    /// decimal already, so no base conversion is needed. Wrap unbuffered
+    /// writers such as files in a [`BufWriter`](std::io::BufWriter) for
+    /// small numbers.
+    ///
+    /// ```
+    /// use karatsuba::BigInt;
+    ///
+    /// let mut out = Vec::new();
+    ///
+    /// BigInt::from(-1234).write_decimal(&mut out).unwrap();
+    ///
+    /// assert_eq!(out, b"-1234");
+    /// ```
+    pub fn write_decimal<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let mut buf = [0u8; CHUNK];
+
+        for chunk in self.digits().chunks(CHUNK) {
+            for (byte, &c) in buf.iter_mut().zip(chunk) {
+                *byte = c as u8;
+            }
+
+            w.write_all(&buf[..chunk.len()])?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a decimal integer from `r`, a chunk at a time, in the format
+    /// `parse` accepts.
+    ///
+    /// Whitespace around the number is skipped, so a file holding one number
+    /// and a newline reads back as written. The text is never held in memory
+    /// in full, only the digits. Malformed input is an
+    /// [`InvalidData`](io::ErrorKind::InvalidData) error wrapping a
+    /// [`ParseBigIntError`], whose position is a byte offset into the stream.
+    ///
+    /// ```
+    /// use karatsuba::BigInt;
+    ///
+    /// let n = BigInt::read_decimal(&b"  -00123\n"[..]).unwrap();
+    ///
+    /// assert_eq!(n, BigInt::from(-123));
+    /// assert!(BigInt::read_decimal(&b"12 34"[..]).is_err());
+    /// ```
+    pub fn read_decimal<R: Read>(mut r: R) -> io::Result<BigInt> {
+        let mut buf = [0u8; CHUNK];
+
+        let mut digits = Vec::new();
+        let mut state = State::Leading;
+        let mut offset = 0;
+
+        loop {
+            let len = match r.read(&mut buf) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            for (i, &byte) in buf[..len].iter().enumerate() {
+                state = match (state, byte) {
+                    (State::Leading, b'-') => {
+                        digits.push('-');
+                        State::Signed
+                    }
+                    (State::Leading, b'+') => State::Signed,
+                    (State::Leading, _) | (State::Trailing, _) if byte.is_ascii_whitespace() => state,
+                    (State::Leading, b'0'..=b'9') | (State::Signed, b'0'..=b'9') | (State::Digits, b'0'..=b'9') => {
+                        digits.push(byte as char);
+                        State::Digits
+                    }
+                    (State::Digits, _) if byte.is_ascii_whitespace() => State::Trailing,
+                    _ => {
+                        return Err(invalid(ParseBigIntError::InvalidDigit {
+                            index: offset + i,
+                            found: if byte.is_ascii() { byte as char } else { char::REPLACEMENT_CHARACTER },
+                        }))
+                    }
+                };
+            }
+
+            offset += len;
+        }
+
+        if state == State::Leading || state == State::Signed {
+            return Err(invalid(ParseBigIntError::Empty));
+        }
+
+        Ok(BigInt::from_digits(digits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::String;
+
+    /// a reader that hands out a few bytes at a time, and is interrupted in between
+    struct Trickle<'a> {
+        data: &'a [u8],
+        interrupt: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+
+            if self.interrupt {
+                return Err(io::Error::from(io::ErrorKind::Interrupted));
+            }
+
+            let len = self.data.len().min(buf.len()).min(3);
+
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+
+            Ok(len)
+        }
+    }
+
+    fn run_read_test(input: &str) -> Result<BigInt, ParseBigIntError> {
+        let result = BigInt::read_decimal(Trickle { data: input.as_bytes(), interrupt: false });
+
+        println!("[run_read_test] {:?} -> {:?}", input, result);
+
+        result.map_err(|e| *e.into_inner().unwrap().downcast::<ParseBigIntError>().unwrap())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let n: BigInt = format!("-{}", "9876543210".repeat(2000)).parse().unwrap();
+
+        let mut out = Vec::new();
+        n.write_decimal(&mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out.clone()).unwrap(), n.to_string());
+        assert_eq!(BigInt::read_decimal(&out[..]).unwrap(), n);
+
+        let mut out = Vec::new();
+        BigInt::zero().write_decimal(&mut out).unwrap();
+
+        assert_eq!(out, b"0");
+    }
+
+    #[test]
+    fn test_read() {
+        assert_eq!(run_read_test("42"), Ok(BigInt::from(42)));
+        assert_eq!(run_read_test("\n\t +00420 \r\n"), Ok(BigInt::from(420)));
+        assert_eq!(run_read_test("-0"), Ok(BigInt::zero()));
+        assert_eq!(run_read_test("-98765432109876543210"), Ok("-98765432109876543210".parse().unwrap()));
+        assert_eq!(run_read_test(""), Err(ParseBigIntError::Empty));
+        assert_eq!(run_read_test("  -"), Err(ParseBigIntError::Empty));
+        assert_eq!(run_read_test("- 1"), Err(ParseBigIntError::InvalidDigit { index: 1, found: ' ' }));
+        assert_eq!(run_read_test("1234x"), Err(ParseBigIntError::InvalidDigit { index: 4, found: 'x' }));
+        assert_eq!(run_read_test(" 12 34"), Err(ParseBigIntError::InvalidDigit { index: 4, found: '3' }));
+        assert_eq!(run_read_test("--1"), Err(ParseBigIntError::InvalidDigit { index: 1, found: '-' }));
+        assert_eq!(run_read_test("1…"), Err(ParseBigIntError::InvalidDigit { index: 1, found: '\u{fffd}' }));
+    }
+
+    #[test]
+    fn test_write_errors() {
+        struct Full;
+
+        impl Write for Full {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let error = BigInt::from(7).write_decimal(Full).unwrap_err();
+
+        assert_eq!(error.to_string(), "disk full");
+    }
+}
@@ -0,0 +1,479 @@
+//! Recording the recursion tree of a multiplication.
+//!
+//! [`BigInt::mul_with_trace`] returns a [`TraceNode`] for the top level
+//! multiplication, whose children are the three sub-products of its
+//! Karatsuba step, and so on down. The tree can be exported as JSON or as
+//! a Graphviz DOT graph:
+//!
+//! ```
+//! use karatsuba::BigInt;
+//!
+//! let a: BigInt = "31415926535897932384626433832795028841971".parse().unwrap();
+//! let b: BigInt = "27182818284590452353602874713526624977572".parse().unwrap();
+//!
+//! let (product, trace) = a.mul_with_trace(&b, 1);
+//!
+//! assert_eq!(trace.result, product.to_string());
+//! assert!(trace.to_dot().starts_with("digraph karatsuba {"));
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::arith::{self, Probe};
+use crate::BigInt;
+
+/// How one multiplication in the tree was carried out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Method {
+    /// Small enough to multiply as machine integers.
+    Native,
+    /// Grade-school multiplication, below the Karatsuba threshold.
+    Schoolbook,
+    /// One Karatsuba step over three smaller products.
+    Karatsuba,
+}
+
+impl Method {
+    /// The lowercase name used in the exported formats.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Method::Native => "native",
+            Method::Schoolbook => "schoolbook",
+            Method::Karatsuba => "karatsuba",
+        }
+    }
+}
+
+/// The intermediate values of one Karatsuba step.
+///
+/// The operands were split as `a = ah * 10^shift + al` and
+/// `b = bh * 10^shift + bl`, giving `z0 = al * bl`,
+/// `z1 = (al + ah) * (bl + bh)`, `z2 = ah * bh` and `z3 = z1 - z2 - z0`, and
+/// the product `z2 * 10^(2 * shift) + z3 * 10^shift + z0`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct KaratsubaStep {
+    /// The high digits of `a`.
+    pub ah: String,
+    /// The low `shift` digits of `a`.
+    pub al: String,
+    /// The high digits of `b`.
+    pub bh: String,
+    /// The low `shift` digits of `b`.
+    pub bl: String,
+    /// The number of high digits kept in `ah` and `bh`, after padding the
+    /// operands to the same length.
+    pub mid: usize,
+    /// The number of low digits split off.
+    pub shift: usize,
+    /// `al * bl`
+    pub z0: String,
+    /// `(al + ah) * (bl + bh)`
+    pub z1: String,
+    /// `ah * bh`
+    pub z2: String,
+    /// `z1 - z2 - z0`
+    pub z3: String,
+}
+
+/// One multiplication in the recursion tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TraceNode {
+    /// The first operand, as passed in.
+    pub a: String,
+    /// The second operand, as passed in.
+    pub b: String,
+    /// How the product was computed.
+    pub method: Method,
+    /// The split, for Karatsuba steps.
+    pub step: Option<KaratsubaStep>,
+    /// The product.
+    pub result: String,
+    /// True if this is a Karatsuba step whose children were cut off by the depth limit.
+    pub pruned: bool,
+    /// The multiplications for `z0`, `z1` and `z2`, in that order.
+    pub children: Vec<TraceNode>,
+}
+
+impl TraceNode {
+    /// Export as a single line of JSON.
+    ///
+    /// Each node is an object with the fields of [`TraceNode`]; `step` is
+    /// `null` for base cases.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json);
+        json
+    }
+
+    fn write_json(&self, json: &mut String) {
+        write!(
+            json,
+            "{{\"a\":\"{}\",\"b\":\"{}\",\"method\":\"{}\",\"result\":\"{}\",\"pruned\":{},\"step\":",
+            self.a,
+            self.b,
+            self.method.name(),
+            self.result,
+            self.pruned
+        )
+        .unwrap();
+
+        match &self.step {
+            None => json.push_str("null"),
+            Some(step) => write!(
+                json,
+                "{{\"ah\":\"{}\",\"al\":\"{}\",\"bh\":\"{}\",\"bl\":\"{}\",\"mid\":{},\"shift\":{},\
+                 \"z0\":\"{}\",\"z1\":\"{}\",\"z2\":\"{}\",\"z3\":\"{}\"}}",
+                step.ah, step.al, step.bh, step.bl, step.mid, step.shift, step.z0, step.z1, step.z2, step.z3
+            )
+            .unwrap(),
+        }
+
+        json.push_str(",\"children\":[");
+
+        for (i, child) in self.children.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+
+            child.write_json(json);
+        }
+
+        json.push_str("]}");
+    }
+
+    /// Export as a Graphviz DOT digraph, one box per multiplication.
+    ///
+    /// Render it with e.g. `dot -Tsvg trace.dot -o trace.svg`.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph karatsuba {\n    node [shape=box, fontname=\"monospace\"];\n");
+        let mut next_id = 0;
+
+        self.write_dot(&mut dot, &mut next_id);
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// writes this subtree and returns the id of its root
+    fn write_dot(&self, dot: &mut String, next_id: &mut usize) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+
+        let mut label = format!("{} x {}\\n= {}\\n{}", self.a, self.b, self.result, self.method.name());
+
+        if let Some(step) = &self.step {
+            write!(
+                label,
+                "\\nah={} al={} bh={} bl={}\\nz0={} z1={} z2={} z3={}",
+                step.ah, step.al, step.bh, step.bl, step.z0, step.z1, step.z2, step.z3
+            )
+            .unwrap();
+        }
+
+        writeln!(dot, "    n{} [label=\"{}\"];", id, label).unwrap();
+
+        for (child, name) in self.children.iter().zip(["z0", "z1", "z2"].iter()) {
+            let child_id = child.write_dot(dot, next_id);
+
+            writeln!(dot, "    n{} -> n{} [label=\"{}\"];", id, child_id, name).unwrap();
+        }
+
+        if self.pruned {
+            let child_id = *next_id;
+            *next_id += 1;
+
+            writeln!(dot, "    n{} [label=\"...\", style=dashed];", child_id).unwrap();
+            writeln!(dot, "    n{} -> n{} [style=dashed];", id, child_id).unwrap();
+        }
+
+        id
+    }
+}
+
+/// `n * 10^shift` written out, without padding zero with zeros
+fn shifted(n: &str, shift: usize) -> String {
+    if n == "0" {
+        String::from(n)
+    } else {
+        format!("{}{}", n, "0".repeat(shift))
+    }
+}
+
+impl TraceNode {
+    /// Narrate the multiplication step by step, each sub-product indented
+    /// under the step that needs it.
+    ///
+    /// See [`BigInt::explain`] for an example.
+    pub fn explain(&self) -> String {
+        let mut text = String::new();
+        self.write_explanation(&mut text, "");
+        text
+    }
+
+    fn write_explanation(&self, text: &mut String, indent: &str) {
+        let step = match &self.step {
+            Some(step) => step,
+            None => {
+                let how = match self.method {
+                    Method::Native if self.a.len() + self.b.len() <= 4 => "",
+                    Method::Native => " (multiplied natively)",
+                    _ => " (multiplied the schoolbook way)",
+                };
+
+                writeln!(text, "{}{} x {} = {}{}", indent, self.a, self.b, self.result, how).unwrap();
+                return;
+            }
+        };
+
+        let inner = format!("{}    ", indent);
+
+        writeln!(text, "{}{} x {}", indent, self.a, self.b).unwrap();
+
+        let a = self.a.trim_start_matches('-');
+        let b = self.b.trim_start_matches('-');
+
+        if a.len() != self.a.len() || b.len() != self.b.len() {
+            writeln!(text, "{}  multiply {} x {}, then fix the sign", indent, a, b).unwrap();
+        }
+
+        let len = step.mid + step.shift;
+
+        if a.len() != len || b.len() != len {
+            writeln!(text, "{}  pad both to {} digits", indent, len).unwrap();
+        }
+
+        writeln!(
+            text,
+            "{}  split into the top mid = {} and the low shift = {} digits:",
+            indent, step.mid, step.shift
+        )
+        .unwrap();
+        writeln!(text, "{}    {} = {} * 10^{} + {}", indent, a, step.ah, step.shift, step.al).unwrap();
+        writeln!(text, "{}    {} = {} * 10^{} + {}", indent, b, step.bh, step.shift, step.bl).unwrap();
+
+        let products = ["z0 = al * bl", "z1 = (al + ah) * (bl + bh)", "z2 = ah * bh"];
+
+        for (i, name) in products.iter().enumerate() {
+            writeln!(text, "{}  {}:", indent, name).unwrap();
+
+            match self.children.get(i) {
+                Some(child) => child.write_explanation(text, &inner),
+                None => writeln!(text, "{}(not recorded)", inner).unwrap(),
+            }
+        }
+
+        writeln!(
+            text,
+            "{}  z3 = z1 - z2 - z0 = {} - {} - {} = {}",
+            indent, step.z1, step.z2, step.z0, step.z3
+        )
+        .unwrap();
+        writeln!(
+            text,
+            "{}  z2 * 10^{} + z3 * 10^{} + z0 = {} + {} + {} = {}",
+            indent,
+            2 * step.shift,
+            step.shift,
+            shifted(&step.z2, 2 * step.shift),
+            shifted(&step.z3, step.shift),
+            step.z0,
+            self.result.trim_start_matches('-')
+        )
+        .unwrap();
+
+        if self.result.starts_with('-') {
+            writeln!(text, "{}  with the sign: {}", indent, self.result).unwrap();
+        }
+    }
+}
+
+impl BigInt {
+    /// Narrate the Karatsuba multiplication of two numbers as it would be
+    /// done by hand, splitting all the way down to single digits.
+    ///
+    /// This ignores the [thresholds](crate::tuning) and records every step,
+    /// so it is only meant for small operands.
+    ///
+    /// ```
+    /// use karatsuba::BigInt;
+    ///
+    /// let text = BigInt::from(1234).explain(&BigInt::from(5678));
+    ///
+    /// assert!(text.starts_with("1234 x 5678\n"));
+    /// assert!(text.contains("    1234 = 12 * 10^2 + 34\n"));
+    /// assert!(text.contains("  z3 = z1 - z2 - z0 = 6164 - 672 - 2652 = 2840\n"));
+    /// assert!(text.ends_with("= 6720000 + 284000 + 2652 = 7006652\n"));
+    /// ```
+    pub fn explain(&self, other: &BigInt) -> String {
+        let mut probe = Probe::classroom();
+
+        arith::multiply_probed(self.digits(), other.digits(), &mut probe);
+
+        probe.into_trace().unwrap().explain()
+    }
+
+    /// Multiply, also recording the recursion tree down to `max_depth`
+    /// Karatsuba steps below the top level.
+    ///
+    /// A `max_depth` of 0 records only the top level multiplication.
+    pub fn mul_with_trace(&self, other: &BigInt, max_depth: usize) -> (BigInt, TraceNode) {
+        let mut probe = Probe::tracing(max_depth);
+
+        let product = arith::multiply_probed(self.digits(), other.digits(), &mut probe);
+
+        (BigInt::from_digits(product), probe.into_trace().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    /// `pattern` repeated to `len` digits
+    fn repeated(pattern: &str, len: usize) -> BigInt {
+        big(&pattern.chars().cycle().take(len).collect::<String>())
+    }
+
+    /// every recorded karatsuba step recombines its children into its result
+    fn check_node(node: &TraceNode) {
+        let product = big(&node.a) * big(&node.b);
+
+        assert_eq!(node.result, product.to_string());
+
+        if let Some(step) = &node.step {
+            assert_eq!(big(&step.z3), big(&step.z1) - big(&step.z2) - big(&step.z0));
+
+            if !node.pruned {
+                assert_eq!(node.children.len(), 3);
+                assert_eq!(node.children[0].result, step.z0);
+                assert_eq!(node.children[1].result, step.z1);
+                assert_eq!(node.children[2].result, step.z2);
+            }
+        }
+
+        for child in &node.children {
+            check_node(child);
+        }
+    }
+
+    fn depth(node: &TraceNode) -> usize {
+        node.children.iter().map(|c| 1 + depth(c)).max().unwrap_or(0)
+    }
+
+    #[test]
+    fn test_trace_tree() {
+        let len = arith::karatsuba_digits(2);
+
+        let a = repeated("31415926535", len);
+        let b = -repeated("27182818284", len);
+
+        let (product, trace) = a.mul_with_trace(&b, 10);
+
+        assert_eq!(product, &a * &b);
+        assert_eq!(trace.method, Method::Karatsuba);
+        assert_eq!(trace.result, product.to_string());
+        assert!(depth(&trace) >= 2);
+
+        check_node(&trace);
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        // the children are karatsuba steps too, so they are the ones pruned
+        let a = repeated("9", arith::karatsuba_digits(1));
+
+        let (_, trace) = a.mul_with_trace(&a, 1);
+
+        assert_eq!(depth(&trace), 1);
+        assert!(!trace.pruned);
+        assert!(trace.children.iter().any(|c| c.pruned));
+
+        let (_, trace) = a.mul_with_trace(&a, 0);
+
+        assert!(trace.pruned);
+        assert!(trace.children.is_empty());
+    }
+
+    #[test]
+    fn test_base_case() {
+        let (_, trace) = big("1234").mul_with_trace(&big("5678"), 3);
+
+        assert_eq!(trace.method, Method::Native);
+        assert_eq!(trace.step, None);
+        assert_eq!(
+            trace.to_json(),
+            "{\"a\":\"1234\",\"b\":\"5678\",\"method\":\"native\",\"result\":\"7006652\",\"pruned\":false,\"step\":null,\"children\":[]}"
+        );
+        assert_eq!(
+            trace.to_dot(),
+            "digraph karatsuba {\n    node [shape=box, fontname=\"monospace\"];\n    n0 [label=\"1234 x 5678\\n= 7006652\\nnative\"];\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let text = big("-12").explain(&big("345"));
+
+        println!("[test_explain]\n{}", text);
+
+        assert!(text.contains("  multiply 12 x 345, then fix the sign\n"));
+        assert!(text.contains("  pad both to 3 digits\n"));
+        assert!(text.contains("    1 x 34 = 34\n"));
+        assert!(text.ends_with("  with the sign: -4140\n"));
+
+        assert_eq!(big("7").explain(&big("8")), "7 x 8 = 56\n");
+    }
+
+    #[test]
+    fn test_classroom_tree() {
+        let a: Vec<char> = "31415926535".chars().collect();
+        let b: Vec<char> = "2718281828".chars().collect();
+
+        let mut probe = Probe::classroom();
+
+        arith::multiply_probed(&a, &b, &mut probe);
+
+        let trace = probe.into_trace().unwrap();
+
+        check_node(&trace);
+
+        // every leaf has a single digit operand
+        fn check_leaves(node: &TraceNode) {
+            if node.children.is_empty() {
+                assert!(node.a.len() == 1 || node.b.len() == 1, "{} x {}", node.a, node.b);
+            }
+
+            node.children.iter().for_each(check_leaves);
+        }
+
+        check_leaves(&trace);
+    }
+
+    #[test]
+    fn test_exports() {
+        let a = repeated("123456789", arith::karatsuba_digits(1));
+
+        let (_, trace) = a.mul_with_trace(&a, 1);
+
+        let json = trace.to_json();
+        let dot = trace.to_dot();
+
+        assert!(json.starts_with("{\"a\":\"123456789"));
+        assert_eq!(json.matches("\"method\":").count(), 4);
+        assert_eq!(json.matches('{').count(), json.matches('}').count());
+        assert_eq!(dot.matches("[label=\"z").count(), 3);
+        assert_eq!(dot.matches("style=dashed];").count(), 6);
+        assert!(dot.contains("[label=\"z1\"]"));
+    }
+}
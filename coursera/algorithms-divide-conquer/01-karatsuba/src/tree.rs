@@ -0,0 +1,161 @@
+use alloc::vec::Vec;
+use core::iter::{Product, Sum};
+
+use crate::BigInt;
+
+/// combine `values` pairwise in a balanced binary tree, so that each
+/// combination is of two results built from the same number of values
+fn balanced<I, F>(values: I, empty: BigInt, mut combine: F) -> BigInt
+where
+    I: IntoIterator<Item = BigInt>,
+    F: FnMut(&BigInt, &BigInt) -> BigInt,
+{
+    // each entry stands for 2^height values, with the heights decreasing
+    // like the bits of a binary counter, so at most log2(n) are pending
+    let mut pending: Vec<(BigInt, u32)> = Vec::new();
+
+    for value in values {
+        let mut value = value;
+        let mut height = 0;
+
+        while let Some((top, top_height)) = pending.pop() {
+            if top_height != height {
+                pending.push((top, top_height));
+                break;
+            }
+
+            value = combine(&top, &value);
+            height += 1;
+        }
+
+        pending.push((value, height));
+    }
+
+    // the leftover subtrees, smallest first
+    let mut result = match pending.pop() {
+        Some((value, _)) => value,
+        None => return empty,
+    };
+
+    while let Some((value, _)) = pending.pop() {
+        result = combine(&value, &result);
+    }
+
+    result
+}
+
+/// Multiply many numbers in a balanced product tree.
+///
+/// Multiplying left to right makes each product one number longer than the
+/// last, which leaves Karatsuba with the most unbalanced operands. A product
+/// tree multiplies pairs, then pairs of products, and so on, so both sides
+/// of each multiplication are about the same length. The product of no
+/// numbers is 1.
+///
+/// `Iterator::product` does the same.
+///
+/// ```
+/// use karatsuba::{product, BigInt};
+///
+/// let factorial = product((1..=25).map(BigInt::from));
+///
+/// assert_eq!(factorial.to_string(), "15511210043330985984000000");
+/// assert_eq!(factorial, (1..=25u32).map(BigInt::from).product());
+/// ```
+pub fn product<I: IntoIterator<Item = BigInt>>(values: I) -> BigInt {
+    balanced(values, BigInt::one(), |a, b| a * b)
+}
+
+/// Add many numbers in a balanced tree, like [`product`] multiplies them.
+///
+/// The sum of no numbers is 0. `Iterator::sum` does the same.
+pub fn sum<I: IntoIterator<Item = BigInt>>(values: I) -> BigInt {
+    balanced(values, BigInt::zero(), |a, b| a + b)
+}
+
+impl Product for BigInt {
+    fn product<I: Iterator<Item = BigInt>>(iter: I) -> BigInt {
+        product(iter)
+    }
+}
+
+impl<'a> Product<&'a BigInt> for BigInt {
+    fn product<I: Iterator<Item = &'a BigInt>>(iter: I) -> BigInt {
+        product(iter.cloned())
+    }
+}
+
+impl Sum for BigInt {
+    fn sum<I: Iterator<Item = BigInt>>(iter: I) -> BigInt {
+        sum(iter)
+    }
+}
+
+impl<'a> Sum<&'a BigInt> for BigInt {
+    fn sum<I: Iterator<Item = &'a BigInt>>(iter: I) -> BigInt {
+        sum(iter.cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{random_bigint, Rng};
+
+    fn run_product_test(values: &[BigInt]) {
+        let expected = values.iter().fold(BigInt::one(), |acc, v| &acc * v);
+
+        println!("[run_product_test] {} values", values.len());
+
+        assert_eq!(product(values.iter().cloned()), expected);
+        assert_eq!(values.iter().product::<BigInt>(), expected);
+    }
+
+    #[test]
+    fn test_product() {
+        let mut rng = Rng::new(46);
+
+        for len in 0..20 {
+            let values: Vec<BigInt> = (0..len)
+                .map(|i| {
+                    let n = random_bigint(rng.below_u64(30) as usize + 1, &mut rng);
+                    if i % 3 == 0 { -n } else { n }
+                })
+                .collect();
+
+            run_product_test(&values);
+        }
+
+        run_product_test(&[BigInt::from(5), BigInt::zero(), BigInt::from(-7)]);
+    }
+
+    #[test]
+    fn test_sum() {
+        let values: Vec<BigInt> = (1..=100).map(BigInt::from).collect();
+
+        assert_eq!(sum(values.clone()), BigInt::from(5050));
+        assert_eq!(values.iter().sum::<BigInt>(), BigInt::from(5050));
+        assert_eq!(sum(values.iter().map(|v| -v)), BigInt::from(-5050));
+        assert_eq!(sum(Vec::new()), BigInt::zero());
+        assert_eq!(Vec::<BigInt>::new().into_iter().product::<BigInt>(), BigInt::one());
+    }
+
+    #[test]
+    fn test_balanced() {
+        // records each combination as "(a b)" to show the shape of the tree
+        let shape = |n: u32| {
+            let mut steps = Vec::new();
+
+            balanced((1..=n).map(BigInt::from), BigInt::zero(), |a, b| {
+                steps.push(alloc::format!("({} {})", a, b));
+                a + b
+            });
+
+            steps.join(" ")
+        };
+
+        assert_eq!(shape(4), "(1 2) (3 4) (3 7)");
+        assert_eq!(shape(5), "(1 2) (3 4) (3 7) (10 5)");
+        assert_eq!(shape(7), "(1 2) (3 4) (3 7) (5 6) (11 7) (10 18)");
+    }
+}
@@ -0,0 +1,293 @@
+//! Crossover points between the multiplication algorithms.
+//!
+//! The thresholds start at compiled-in defaults. With the `std` feature they
+//! are replaced on first use by the values in the config file at
+//! [`config_path`], which [`tune`] can measure and `karatsuba --tune` writes.
+//! The crate's own unit tests skip the file, so they run against the defaults
+//! whatever the machine has tuned.
+//!
+//! The file is a list of `key = value` lines, and `#` starts a comment:
+//!
+//! ```text
+//! # written by karatsuba --tune
+//! karatsuba = 24
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Below this many digits the Karatsuba step cannot shrink its operands.
+pub const MIN_KARATSUBA_THRESHOLD: usize = 4;
+
+const DEFAULT_KARATSUBA_THRESHOLD: usize = 16;
+
+static KARATSUBA_THRESHOLD: AtomicUsize = AtomicUsize::new(DEFAULT_KARATSUBA_THRESHOLD);
+
+/// The operand sizes at which each multiplication algorithm takes over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Thresholds {
+    /// Operands with fewer digits than this use schoolbook multiplication
+    /// instead of a Karatsuba step. Never less than [`MIN_KARATSUBA_THRESHOLD`].
+    pub karatsuba: usize,
+}
+
+impl Default for Thresholds {
+    fn default() -> Thresholds {
+        Thresholds {
+            karatsuba: DEFAULT_KARATSUBA_THRESHOLD,
+        }
+    }
+}
+
+/// The error returned when a thresholds config file cannot be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseThresholdsError {
+    /// The 1-based line that could not be parsed.
+    pub line: usize,
+}
+
+impl fmt::Display for ParseThresholdsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid threshold on line {}", self.line)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseThresholdsError {}
+
+impl Thresholds {
+    /// Parse the config file format, keeping defaults for missing keys.
+    ///
+    /// Unknown keys are ignored so that older builds can read newer files.
+    pub fn from_config(config: &str) -> Result<Thresholds, ParseThresholdsError> {
+        let mut thresholds = Thresholds::default();
+
+        for (i, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = ParseThresholdsError { line: i + 1 };
+
+            let (key, value) = line.split_once('=').ok_or_else(|| error.clone())?;
+
+            if key.trim() == "karatsuba" {
+                thresholds.karatsuba = value.trim().parse().map_err(|_| error.clone())?;
+
+                if thresholds.karatsuba < MIN_KARATSUBA_THRESHOLD {
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(thresholds)
+    }
+
+    /// Format as a config file that [`Thresholds::from_config`] reads back.
+    pub fn to_config(&self) -> String {
+        format!("# written by karatsuba --tune\nkaratsuba = {}\n", self.karatsuba)
+    }
+
+    /// Read a config file.
+    #[cfg(feature = "std")]
+    pub fn load(path: &std::path::Path) -> std::io::Result<Thresholds> {
+        let config = std::fs::read_to_string(path)?;
+
+        Thresholds::from_config(&config)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write a config file, creating its directory if needed.
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        std::fs::write(path, self.to_config())
+    }
+}
+
+/// The thresholds currently in effect.
+pub fn thresholds() -> Thresholds {
+    #[cfg(all(feature = "std", not(test)))]
+    load_config_once();
+
+    Thresholds {
+        karatsuba: KARATSUBA_THRESHOLD.load(Ordering::Relaxed),
+    }
+}
+
+/// Replace the thresholds in effect, e.g. with the result of [`tune`].
+///
+/// Values below the minimums are raised to them.
+pub fn set_thresholds(thresholds: Thresholds) {
+    #[cfg(all(feature = "std", not(test)))]
+    load_config_once();
+
+    let karatsuba = core::cmp::max(thresholds.karatsuba, MIN_KARATSUBA_THRESHOLD);
+
+    KARATSUBA_THRESHOLD.store(karatsuba, Ordering::Relaxed);
+}
+
+/// Where the thresholds are loaded from: `$KARATSUBA_CONFIG` if set, otherwise
+/// `karatsuba/thresholds.conf` under `$XDG_CONFIG_HOME` or `~/.config`.
+#[cfg(feature = "std")]
+pub fn config_path() -> Option<std::path::PathBuf> {
+    use std::env;
+    use std::path::PathBuf;
+
+    if let Some(path) = env::var_os("KARATSUBA_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join("karatsuba").join("thresholds.conf"))
+}
+
+/// a missing or unreadable file leaves the compiled-in defaults in place
+#[cfg(all(feature = "std", not(test)))]
+fn load_config_once() {
+    static LOAD: std::sync::Once = std::sync::Once::new();
+
+    LOAD.call_once(|| {
+        if let Some(loaded) = config_path().and_then(|path| Thresholds::load(&path).ok()) {
+            KARATSUBA_THRESHOLD.store(loaded.karatsuba, Ordering::Relaxed);
+        }
+    });
+}
+
+/// Time schoolbook multiplication against a Karatsuba step on this machine.
+///
+/// For growing operand sizes, compares one Karatsuba step over schoolbook
+/// halves against plain schoolbook, and returns the smallest size from which
+/// Karatsuba wins twice in a row. This takes well under a second in release
+/// builds and does not change the thresholds in effect.
+#[cfg(feature = "std")]
+pub fn tune() -> Thresholds {
+    use crate::inplace;
+    use std::time::{Duration, Instant};
+
+    const SIZES: [usize; 12] = [4, 6, 8, 12, 16, 24, 32, 48, 64, 96, 128, 192];
+
+    /// average time of `mul_into` with the given threshold over at least a few milliseconds
+    fn time(n: usize, threshold: usize) -> Duration {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+
+        let mut digits = || {
+            (0..n)
+                .map(|_| {
+                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    core::char::from_digit(((seed >> 33) % 10) as u32, 10).unwrap()
+                })
+                .collect::<alloc::vec::Vec<char>>()
+        };
+
+        let a = digits();
+        let b = digits();
+
+        let mut dst = alloc::vec!['0'; 2 * n];
+        let mut scratch = alloc::vec!['0'; inplace::scratch_len(n, n, threshold)];
+
+        let start = Instant::now();
+        let mut runs = 0;
+
+        while runs < 3 || start.elapsed() < Duration::from_millis(2) {
+            inplace::multiply_with(&mut dst, &a, &b, &mut scratch, threshold);
+            runs += 1;
+        }
+
+        start.elapsed() / runs
+    }
+
+    let mut wins = 0;
+
+    for (i, &n) in SIZES.iter().enumerate() {
+        // a threshold above n forces schoolbook, a threshold of n forces one step
+        let schoolbook = time(n, n + 1);
+        let karatsuba = time(n, n);
+
+        if karatsuba < schoolbook {
+            wins += 1;
+
+            if wins == 2 {
+                return Thresholds {
+                    karatsuba: SIZES[i - 1],
+                };
+            }
+        } else {
+            wins = 0;
+        }
+    }
+
+    Thresholds {
+        karatsuba: SIZES[SIZES.len() - 1],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_round_trip() {
+        let thresholds = Thresholds { karatsuba: 42 };
+
+        assert_eq!(Thresholds::from_config(&thresholds.to_config()), Ok(thresholds));
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = "\n# comment\n  karatsuba=  30  # trailing\nfuture = 7\n";
+
+        assert_eq!(Thresholds::from_config(config), Ok(Thresholds { karatsuba: 30 }));
+        assert_eq!(Thresholds::from_config(""), Ok(Thresholds::default()));
+        assert_eq!(
+            Thresholds::from_config("karatsuba = 3"),
+            Err(ParseThresholdsError { line: 1 })
+        );
+        assert_eq!(
+            Thresholds::from_config("\nkaratsuba = lots"),
+            Err(ParseThresholdsError { line: 2 })
+        );
+        assert_eq!(
+            Thresholds::from_config("karatsuba"),
+            Err(ParseThresholdsError { line: 1 })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("karatsuba-tuning-{}", std::process::id()))
+            .join("thresholds.conf");
+
+        let thresholds = Thresholds { karatsuba: 20 };
+
+        thresholds.save(&path).unwrap();
+
+        assert_eq!(Thresholds::load(&path).unwrap(), thresholds);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_tune() {
+        let tuned = tune();
+
+        println!("[test_tune] karatsuba threshold = {}", tuned.karatsuba);
+
+        assert!(tuned.karatsuba >= MIN_KARATSUBA_THRESHOLD);
+        assert!(tuned.karatsuba <= 192);
+    }
+}
@@ -0,0 +1,191 @@
+//! Runs the arithmetic test vectors in `tests/vectors/*.vec`.
+//!
+//! Each line of a vector file is a case: an operation, its operands and the
+//! expected result after `=`, e.g. `mul -1234 4321 = -5332114`. Operands are
+//! passed to the digit functions exactly as written, leading zeros and all,
+//! and the result must match the expected text exactly. Blank lines and lines
+//! starting with `#` are ignored.
+//!
+//! To support a new operation, add it to [`OPS`].
+
+use std::fs;
+use std::path::Path;
+
+use crate::arith::{self, v2s};
+
+/// an operation: its name, how many operands it takes and how to compute it
+type Op = (&'static str, usize, fn(&[Vec<char>]) -> String);
+
+const OPS: &[Op] = &[
+    ("add", 2, |x| v2s(&arith::add(&x[0], &x[1]))),
+    ("sub", 2, |x| v2s(&arith::subtract(&x[0], &x[1]))),
+    ("mul", 2, |x| v2s(&arith::multiply(&x[0], &x[1]))),
+    ("div", 2, |x| v2s(&arith::quotient(&x[0], &x[1]))),
+    ("rem", 2, |x| v2s(&arith::remainder(&x[0], &x[1]))),
+    ("ge", 2, |x| arith::larger(&x[0], &x[1]).to_string()),
+];
+
+/// one line of a vector file
+#[derive(Debug, PartialEq)]
+struct Case {
+    op: String,
+    operands: Vec<Vec<char>>,
+    expected: String,
+}
+
+/// parse a line, returning `None` for blank lines and comments
+fn parse_case(line: &str) -> Option<Result<Case, String>> {
+    let line = line.trim();
+
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    Some(parse_words(line))
+}
+
+fn parse_words(line: &str) -> Result<Case, String> {
+    let (lhs, expected) = line.split_once('=').ok_or("missing '= expected'")?;
+
+    let mut words = lhs.split_whitespace();
+
+    let op = words.next().ok_or("missing operation")?;
+
+    let operands = words
+        .map(|word| {
+            let digits = word.strip_prefix('-').unwrap_or(word);
+
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                Err(format!("invalid operand {}", word))
+            } else {
+                Ok(word.chars().collect())
+            }
+        })
+        .collect::<Result<Vec<Vec<char>>, String>>()?;
+
+    let expected = expected.trim();
+
+    if expected.is_empty() || expected.contains(char::is_whitespace) {
+        return Err(format!("invalid expected result {:?}", expected));
+    }
+
+    Ok(Case {
+        op: String::from(op),
+        operands,
+        expected: String::from(expected),
+    })
+}
+
+/// run a case, returning what it computed
+fn run_case(case: &Case) -> Result<String, String> {
+    let &(_, arity, f) = OPS
+        .iter()
+        .find(|(name, _, _)| *name == case.op)
+        .ok_or_else(|| format!("unknown operation {}", case.op))?;
+
+    if case.operands.len() != arity {
+        return Err(format!("{} takes {} operands, got {}", case.op, arity, case.operands.len()));
+    }
+
+    Ok(f(&case.operands))
+}
+
+/// run every case in a file, returning a description of each failure
+fn run_file(path: &Path) -> (usize, Vec<String>) {
+    let name = path.file_name().unwrap().to_string_lossy();
+    let text = fs::read_to_string(path).unwrap();
+
+    let mut passed = 0;
+    let mut failures = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let location = format!("{}:{}", name, i + 1);
+
+        let outcome = match parse_case(line) {
+            None => continue,
+            Some(case) => case.and_then(|case| {
+                let actual = run_case(&case)?;
+
+                if actual == case.expected {
+                    Ok(())
+                } else {
+                    Err(format!("expected {}, got {}", case.expected, actual))
+                }
+            }),
+        };
+
+        match outcome {
+            Ok(()) => {
+                println!("[run_file] {} ok", location);
+                passed += 1;
+            }
+            Err(message) => {
+                println!("[run_file] {} FAILED: {}", location, message);
+                failures.push(format!("{}: {}", location, message));
+            }
+        }
+    }
+
+    (passed, failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector_files() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("vectors");
+
+        let mut paths: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "vec"))
+            .collect();
+
+        paths.sort();
+
+        assert!(!paths.is_empty(), "no vector files in {}", dir.display());
+
+        let mut passed = 0;
+        let mut failures = Vec::new();
+
+        for path in &paths {
+            let (file_passed, file_failures) = run_file(path);
+
+            passed += file_passed;
+            failures.extend(file_failures);
+        }
+
+        println!("[test_vector_files] {} passed, {} failed", passed, failures.len());
+
+        assert!(failures.is_empty(), "failing vectors:\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_parse_case() {
+        assert_eq!(parse_case("  # comment"), None);
+        assert_eq!(parse_case(""), None);
+        assert_eq!(
+            parse_case("mul -12 034 = -408"),
+            Some(Ok(Case {
+                op: String::from("mul"),
+                operands: vec![vec!['-', '1', '2'], vec!['0', '3', '4']],
+                expected: String::from("-408"),
+            }))
+        );
+        assert_eq!(parse_case("mul 1 2"), Some(Err(String::from("missing '= expected'"))));
+        assert_eq!(parse_case("mul 1 x = 2"), Some(Err(String::from("invalid operand x"))));
+        assert_eq!(parse_case("mul 1 - = 2"), Some(Err(String::from("invalid operand -"))));
+        assert_eq!(parse_case("mul 1 2 ="), Some(Err(String::from("invalid expected result \"\""))));
+    }
+
+    #[test]
+    fn test_run_case() {
+        let case = |line| parse_case(line).unwrap().unwrap();
+
+        assert_eq!(run_case(&case("ge 3 3 = true")), Ok(String::from("true")));
+        assert_eq!(run_case(&case("pow 2 3 = 8")), Err(String::from("unknown operation pow")));
+        assert_eq!(run_case(&case("add 1 = 1")), Err(String::from("add takes 2 operands, got 1")));
+    }
+}
@@ -0,0 +1,165 @@
+//! Run the `karatsuba` binary the way scripts do.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// run the binary with `args` and `stdin`, returning its exit code, stdout and stderr
+///
+/// The config path points at a file that does not exist, so the binary runs
+/// with the default thresholds whatever this machine has tuned.
+fn run(args: &[&str], stdin: impl AsRef<[u8]>) -> (i32, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_karatsuba"))
+        .args(args)
+        .env("KARATSUBA_CONFIG", Path::new(env!("CARGO_MANIFEST_DIR")).join("target").join("no-such-thresholds.conf"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run karatsuba");
+
+    child.stdin.take().unwrap().write_all(stdin.as_ref()).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+
+    println!("[run] {:?} -> {:?}", args, output.status);
+
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn test_commands() {
+    assert_eq!(run(&["mul", "1234", "-5678"], ""), (0, String::from("-7006652\n"), String::new()));
+    assert_eq!(run(&["add", "99999999999999999999", "1"], "").1, "100000000000000000000\n");
+    assert_eq!(run(&["sub", "-", "-"], "5\n  12\n").1, "-7\n");
+    assert_eq!(run(&["mul", "3"], "-4").1, "-12\n");
+    assert_eq!(run(&["mul", "--verify", "11", "11"], "").1, "121\n");
+    assert!(run(&["mul", "--verify", "--trace", "json", "11", "11"], "").1.contains("\"result\""));
+    assert!(run(&["explain", "12", "34"], "").1.starts_with("12 x 34\n"));
+    assert!(run(&[], "").1.contains(" x "));
+}
+
+#[test]
+fn test_help() {
+    for args in [&["help"][..], &["--help"], &["-h", "12"], &["mul", "12", "--help"]] {
+        let (code, stdout, _) = run(args, "");
+
+        assert_eq!(code, 0);
+        assert!(stdout.starts_with("usage: karatsuba"), "{}", stdout);
+    }
+
+    // help past the first argument belongs to the command
+    assert!(run(&["calc", "help"], "").1.starts_with("expressions:"));
+
+    let (code, _, stderr) = run(&["mul", "@help", "2"], "");
+
+    assert_eq!(code, 2);
+    assert!(stderr.contains("cannot read help"), "{}", stderr);
+}
+
+#[test]
+fn test_calc() {
+    assert_eq!(run(&["calc", "(2^64 - 1) % 1000"], "").1, "615\n");
+    assert_eq!(run(&["calc"], "let x = 6\nx * 7\n\nquit\n1\n").1, "x = 6\n42\n");
+
+    let (code, _, stderr) = run(&["calc", "1 / 0"], "");
+
+    assert_eq!(code, 2);
+    assert!(stderr.contains("  1 / 0\n      ^ division by zero"), "{}", stderr);
+
+    let (code, stdout, _) = run(&["calc"], "2 +\n");
+
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "2 +\n   ^ expected a number, variable or '(', found end of input\n");
+}
+
+#[test]
+fn test_batch() {
+    let jobs = "mul 12 34\n\n{\"id\": \"x\", \"op\": \"div\", \"a\": \"1\", \"b\": \"0\"}\nnot a job\n{\"op\"\n";
+
+    let (code, stdout, stderr) = run(&["--batch"], jobs);
+
+    assert_eq!((code, stderr.as_str()), (0, ""));
+    assert_eq!(
+        stdout,
+        "1 ok 408\n\
+         {\"id\":\"x\",\"error\":\"division by zero\"}\n\
+         4 error unknown operation not\n\
+         {\"id\":5,\"error\":\"invalid JSON: expected ':' but the line ended\"}\n"
+    );
+
+    let (code, stdout, _) = run(&["batch"], b"mul 2 3\nmul \xff 2\nmul 4 5\n");
+
+    assert_eq!((code, stdout.as_str()), (0, "1 ok 6\n2 error invalid UTF-8\n3 ok 20\n"));
+}
+
+#[test]
+fn test_fuzz() {
+    let (code, stdout, _) = run(&["fuzz", "--seed", "7", "--cases", "20"], "");
+
+    assert_eq!((code, stdout.as_str()), (0, "fuzzing 20 cases with seed 7\nok\n"));
+    assert_eq!(run(&["fuzz", "--seed", "x"], "").0, 2);
+    assert_eq!(run(&["fuzz", "--depth", "3"], "").0, 2);
+}
+
+#[test]
+fn test_files() {
+    let dir = std::env::temp_dir().join(format!("karatsuba-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let input = dir.join("a.txt");
+    let output = dir.join("product.txt");
+
+    std::fs::write(&input, "123456789\n").unwrap();
+
+    let a = format!("@{}", input.display());
+    let (code, stdout, _) = run(&["mul", &a, "1000", "-o", output.to_str().unwrap()], "");
+
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "");
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "123456789000\n");
+
+    std::fs::write(&input, "12 34\n").unwrap();
+
+    let (code, _, stderr) = run(&["mul", &a, "2"], "");
+
+    assert_eq!(code, 2);
+    assert!(stderr.contains("invalid number in"), "{}", stderr);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_errors() {
+    let (code, stdout, stderr) = run(&["mul", "12x", "3"], "");
+
+    assert_eq!(code, 2);
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("invalid number \"12x\""), "{}", stderr);
+
+    assert_eq!(run(&["mul", "1"], "").0, 2);
+    assert_eq!(run(&["mul", "1", "2", "3"], "").0, 2);
+    assert_eq!(run(&["mul", "@/does/not/exist", "2"], "").0, 2);
+    assert_eq!(run(&["div", "1", "2"], "").0, 2);
+    assert_eq!(run(&["mul", "--trace", "svg", "1", "2"], "").0, 2);
+
+    let (code, _, stderr) = run(&["add", "--verify", "1", "2"], "");
+
+    assert_eq!(code, 2);
+    assert!(stderr.contains("--verify only applies to mul"), "{}", stderr);
+    assert_eq!(run(&["explain", "--verify", "1", "2"], "").0, 2);
+
+    let (code, stdout, stderr) = run(&["add", "1", "2", "--stats"], "");
+
+    assert_eq!((code, stdout.as_str()), (2, ""));
+    assert!(stderr.contains("--stats only applies to mul"), "{}", stderr);
+    assert_eq!(run(&["sub", "--stats", "1", "2"], "").0, 2);
+    assert_eq!(run(&["explain", "--stats", "1", "2"], "").0, 2);
+    assert_eq!(run(&["mul", "--stats", "--trace", "json", "1", "2"], "").0, 2);
+    assert_eq!(run(&["mul", "--stats", "1", "2"], "").0, 0);
+    assert_eq!(run(&["mul", "1", "2", "-o", "/does/not/exist/out"], "").0, 1);
+}
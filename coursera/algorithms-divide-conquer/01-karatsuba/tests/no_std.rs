@@ -0,0 +1,25 @@
+//! Check that the library still builds as `#![no_std]` with only `alloc`.
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_builds_without_std() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    // a separate target directory avoids waiting on the lock held by this test run
+    let output = Command::new(env!("CARGO"))
+        .args(["check", "--lib", "--no-default-features", "--offline", "--quiet"])
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(manifest_dir.join("target").join("no_std"))
+        .output()
+        .expect("failed to run cargo");
+
+    assert!(
+        output.status.success(),
+        "no_std build failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}